@@ -0,0 +1,107 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DeriveInput, Expr, Fields, LitInt};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let mut strategies = Vec::new();
+    match &input.data {
+        Data::Enum(data) => {
+            for variant in &data.variants {
+                let options = Options::of(&variant.attrs)?;
+                if options.skip {
+                    continue;
+                }
+                let ident = &variant.ident;
+                let strategy = fields(quote!(Self::#ident), &variant.fields)?;
+                let weight = options.weight;
+                strategies.push(quote!((#weight, #strategy)));
+            }
+        }
+        Data::Struct(data) => {
+            let strategy = fields(quote!(Self), &data.fields)?;
+            strategies.push(quote!((1, #strategy)));
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(Span::call_site(), "ArbitraryErr can't be derived for unions"));
+        }
+    }
+    if strategies.is_empty() {
+        return Err(syn::Error::new(Span::call_site(), "ArbitraryErr needs at least one variant that isn't skipped"));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::match_err::__private::proptest::arbitrary::Arbitrary for #name #ty_generics #where_clause {
+            type Parameters = ();
+            type Strategy = ::match_err::__private::proptest::strategy::BoxedStrategy<Self>;
+
+            fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                ::match_err::__private::proptest::strategy::Strategy::boxed(::match_err::__private::proptest::strategy::Union::new_weighted(
+                    ::std::vec![#(#strategies),*],
+                ))
+            }
+        }
+    })
+}
+
+/// A boxed strategy building the variant from strategies for each of its fields
+fn fields(path: TokenStream, fields: &Fields) -> syn::Result<TokenStream> {
+    let mut bindings = Vec::new();
+    let mut strategies = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        bindings.push(field.ident.clone().unwrap_or_else(|| format_ident!("field{}", i)));
+        strategies.push(match Options::of(&field.attrs)?.strategy {
+            Some(strategy) => quote!(#strategy),
+            None => {
+                let ty = &field.ty;
+                quote!(::match_err::__private::proptest::arbitrary::any::<#ty>())
+            }
+        });
+    }
+
+    let value = match fields {
+        Fields::Unit => quote!(#path),
+        Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+        Fields::Named(_) => quote!(#path { #(#bindings),* }),
+    };
+    if bindings.is_empty() {
+        return Ok(quote! {
+            ::match_err::__private::proptest::strategy::Strategy::boxed(::match_err::__private::proptest::strategy::LazyJust::new(|| #value))
+        });
+    }
+    Ok(quote! {
+        ::match_err::__private::proptest::strategy::Strategy::boxed(::match_err::__private::proptest::strategy::Strategy::prop_map(
+            (#(#strategies,)*),
+            |(#(#bindings,)*)| #value,
+        ))
+    })
+}
+
+/// The options of `#[proptest(skip)]`, `#[proptest(weight = n)]` and `#[proptest(strategy = expr)]`
+struct Options {
+    skip: bool,
+    weight: LitInt,
+    strategy: Option<Expr>,
+}
+
+impl Options {
+    fn of(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Options { skip: false, weight: LitInt::new("1", Span::call_site()), strategy: None };
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("proptest")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    options.skip = true;
+                } else if meta.path.is_ident("weight") {
+                    options.weight = meta.value()?.parse()?;
+                } else if meta.path.is_ident("strategy") {
+                    options.strategy = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("expected `skip`, `weight = n` or `strategy = expr`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
@@ -0,0 +1,62 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Ident};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let default = class(&input.attrs)?.unwrap_or_else(unknown);
+
+    let body = match &input.data {
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let class = class(&variant.attrs)?.unwrap_or_else(|| default.clone());
+                arms.push(quote!(Self::#ident { .. } => #class,));
+            }
+            quote! {
+                match self {
+                    #(#arms)*
+                    #[allow(unreachable_patterns)]
+                    _ => #default,
+                }
+            }
+        }
+        Data::Struct(_) => default,
+        Data::Union(_) => {
+            return Err(syn::Error::new(Span::call_site(), "Classify can't be derived for unions"));
+        }
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::match_err::Classify for #name #ty_generics #where_clause {
+            fn class(&self) -> ::match_err::ErrorClass {
+                #body
+            }
+        }
+    })
+}
+
+fn unknown() -> TokenStream {
+    quote!(::match_err::ErrorClass::Unknown)
+}
+
+/// Reads the class of a `#[class(transient)]`, `#[class(permanent)]` or `#[class(unknown)]` attribute
+fn class(attrs: &[Attribute]) -> syn::Result<Option<TokenStream>> {
+    let mut class = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("class")) {
+        if class.is_some() {
+            return Err(syn::Error::new_spanned(attr, "duplicate class attribute"));
+        }
+        let ident: Ident = attr.parse_args()?;
+        let variant = match ident.to_string().as_str() {
+            "transient" => quote!(Transient),
+            "permanent" => quote!(Permanent),
+            "unknown" => quote!(Unknown),
+            _ => return Err(syn::Error::new_spanned(ident, "expected `transient`, `permanent` or `unknown`")),
+        };
+        class = Some(quote!(::match_err::ErrorClass::#variant));
+    }
+    Ok(class)
+}
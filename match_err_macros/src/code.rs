@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Ident, Lit};
+
+use crate::derive::snake_case;
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let body = match &input.data {
+        Data::Enum(data) => {
+            if let Some(attr) = input.attrs.iter().find(|attr| attr.path().is_ident("code")) {
+                return Err(syn::Error::new_spanned(attr, "the codes of an enum are given on its variants"));
+            }
+            let mut used = HashMap::new();
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let (code, shown) = code(&variant.attrs, ident)?;
+                if let Some(other) = used.insert(shown.clone(), ident) {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        format!("duplicate code `{shown}`, already used by `{other}`"),
+                    ));
+                }
+                arms.push(quote!(Self::#ident { .. } => #code,));
+            }
+            quote! {
+                match *self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Struct(_) => code(&input.attrs, &input.ident)?.0,
+        Data::Union(_) => {
+            return Err(syn::Error::new(Span::call_site(), "ErrorCode can't be derived for unions"));
+        }
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::match_err::ErrorCode for #name #ty_generics #where_clause {
+            fn code(&self) -> ::match_err::Code {
+                #body
+            }
+        }
+    })
+}
+
+/// Reads the code of a `#[code("NOT_FOUND")]` or `#[code(404)]` attribute, defaulting to the name
+/// in screaming snake case. Returns the code and how it's shown for the duplicate check
+fn code(attrs: &[Attribute], ident: &Ident) -> syn::Result<(TokenStream, String)> {
+    let mut code = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("code")) {
+        if code.is_some() {
+            return Err(syn::Error::new_spanned(attr, "duplicate code attribute"));
+        }
+        code = Some(match attr.parse_args()? {
+            Lit::Str(lit) => {
+                let shown = format!("{:?}", lit.value());
+                (quote!(::match_err::Code::Str(#lit)), shown)
+            }
+            Lit::Int(lit) => {
+                let num: u32 = lit.base10_parse()?;
+                (quote!(::match_err::Code::Num(#num)), num.to_string())
+            }
+            lit => return Err(syn::Error::new_spanned(lit, "expected a string or an integer code")),
+        });
+    }
+    Ok(code.unwrap_or_else(|| {
+        let name = snake_case(ident).to_uppercase();
+        let shown = format!("{name:?}");
+        (quote!(::match_err::Code::Str(#name)), shown)
+    }))
+}
@@ -0,0 +1,169 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Data, DataEnum, DeriveInput, Fields, Ident};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(Span::call_site(), "MatchErr can only be derived for enums"));
+    };
+
+    let mut methods = Vec::new();
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let name = snake_case(ident);
+        let is = format_ident!("is_{}", name);
+        let is_doc = format!("Returns `true` if the error is [`Self::{ident}`]");
+
+        let pattern = match &variant.fields {
+            Fields::Unit => quote!(Self::#ident),
+            Fields::Unnamed(_) => quote!(Self::#ident(..)),
+            Fields::Named(_) => quote!(Self::#ident { .. }),
+        };
+        methods.push(quote! {
+            #[doc = #is_doc]
+            pub fn #is(&self) -> bool {
+                ::core::matches!(self, #pattern)
+            }
+        });
+
+        let (bindings, types): (Vec<Ident>, Vec<&syn::Type>) = match &variant.fields {
+            Fields::Unit => continue,
+            Fields::Unnamed(fields) => fields
+                .unnamed
+                .iter()
+                .enumerate()
+                .map(|(i, field)| (format_ident!("field{}", i), &field.ty))
+                .unzip(),
+            Fields::Named(fields) => fields
+                .named
+                .iter()
+                .map(|field| (field.ident.clone().unwrap(), &field.ty))
+                .unzip(),
+        };
+        let pattern = match &variant.fields {
+            Fields::Named(_) => quote!(Self::#ident { #(#bindings),* }),
+            _ => quote!(Self::#ident(#(#bindings),*)),
+        };
+        let (refs, owned, values) = if bindings.len() == 1 {
+            let ty = types[0];
+            (quote!(&#ty), quote!(#ty), quote!(#(#bindings)*))
+        } else {
+            (quote!((#(&#types),*)), quote!((#(#types),*)), quote!((#(#bindings),*)))
+        };
+
+        let as_ = format_ident!("as_{}", name);
+        let into = format_ident!("into_{}", name);
+        let as_doc = format!("Returns references to the payload if the error is [`Self::{ident}`]");
+        let into_doc = format!("Returns the payload if the error is [`Self::{ident}`]");
+        methods.push(quote! {
+            #[doc = #as_doc]
+            pub fn #as_(&self) -> ::core::option::Option<#refs> {
+                match self {
+                    #pattern => ::core::option::Option::Some(#values),
+                    #[allow(unreachable_patterns)]
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            #[doc = #into_doc]
+            pub fn #into(self) -> ::core::option::Option<#owned> {
+                match self {
+                    #pattern => ::core::option::Option::Some(#values),
+                    #[allow(unreachable_patterns)]
+                    _ => ::core::option::Option::None,
+                }
+            }
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let variants = data.variants.iter().map(|variant| variant.ident.to_string());
+    let names = data.variants.iter().map(|variant| {
+        let ident = &variant.ident;
+        let name = ident.to_string();
+        quote!(Self::#ident { .. } => #name,)
+    });
+    let transparent = transparent(data)?;
+    Ok(quote! {
+        impl #impl_generics ::match_err::__private::Transparent for #name #ty_generics #where_clause {
+            fn transparent(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
+                #[allow(unused_imports)]
+                use ::match_err::__private::{ViaDowncastable as _, ViaError as _};
+                match self {
+                    #(#transparent)*
+                    #[allow(unreachable_patterns)]
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+
+        impl #impl_generics ::match_err::__private::Variants for #name #ty_generics #where_clause {
+            const VARIANTS: &'static [&'static str] = &[#(#variants),*];
+        }
+
+        impl #impl_generics ::match_err::VariantName for #name #ty_generics #where_clause {
+            fn variant_name(&self) -> &'static str {
+                match *self {
+                    #(#names)*
+                }
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#methods)*
+
+            /// Downcasts any supported error value to this error type
+            pub fn from_any<D>(err: &D) -> ::core::option::Option<&Self>
+            where
+                D: ::match_err::Downcastable + ?::core::marker::Sized,
+                Self: ::core::error::Error + ::core::marker::Send + ::core::marker::Sync + 'static,
+            {
+                ::match_err::Downcastable::downcast_ref::<Self>(err)
+            }
+        }
+    })
+}
+
+/// Returns the arms giving the inner error of the `#[error(transparent)]` variants
+fn transparent(data: &DataEnum) -> syn::Result<Vec<TokenStream>> {
+    let mut arms = Vec::new();
+    for variant in &data.variants {
+        let is_transparent = variant.attrs.iter().any(|attr| {
+            attr.path().is_ident("error") && attr.parse_args::<Ident>().is_ok_and(|arg| arg == "transparent")
+        });
+        if !is_transparent {
+            continue;
+        }
+        let ident = &variant.ident;
+        let pattern = match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => quote!(Self::#ident(inner)),
+            Fields::Named(fields) if fields.named.len() == 1 => {
+                let field = &fields.named[0].ident;
+                quote!(Self::#ident { #field: inner })
+            }
+            _ => return Err(syn::Error::new_spanned(variant, "a transparent variant needs exactly one field")),
+        };
+        arms.push(quote!(#pattern => (&::match_err::__private::Probe(inner)).match_err_dyn(),));
+    }
+    Ok(arms)
+}
+
+/// Converts a variant name like `NotFound` or `HTTPError` into `not_found` or `http_error`
+pub(crate) fn snake_case(ident: &Ident) -> String {
+    let chars: Vec<char> = ident.to_string().chars().collect();
+    let mut name = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let after_lower = i > 0 && (chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit());
+            let before_lower = i > 0 && chars[i - 1].is_uppercase() && chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if after_lower || before_lower {
+                name.push('_');
+            }
+            name.extend(c.to_lowercase());
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
@@ -0,0 +1,155 @@
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::parse::{ParseStream, Parser};
+use syn::spanned::Spanned;
+use syn::{braced, Attribute, Expr, Ident, Pat, Token, Type};
+
+use crate::input::Krate;
+
+/// Finds the mistake in the input of `match_err!` that none of its rules matched, `input ..` for
+/// the whole input and `arms ..` for the remaining arms, and reports it at its span
+pub fn expand(input: TokenStream) -> syn::Error {
+    let parsed = Parser::parse2(
+        |input: ParseStream| {
+            input.parse::<Krate>()?;
+            let mode: Ident = input.parse()?;
+            if mode == "arms" {
+                arms(input)
+            } else {
+                prefix(input)?;
+                whole(input)
+            }
+        },
+        input,
+    );
+    match parsed {
+        Ok(()) => syn::Error::new(Span::call_site(), "invalid input of match_err!"),
+        Err(err) => err,
+    }
+}
+
+/// Skips `deep`, `depth = n` or `transparent(..)` in front of the error
+fn prefix(input: ParseStream) -> syn::Result<()> {
+    let fork = input.fork();
+    let Ok(ident) = fork.parse::<Ident>() else {
+        return Ok(());
+    };
+    if ident == "deep" && !fork.peek(Token![,]) && !fork.peek(Token![.]) {
+        input.parse::<Ident>()?;
+    } else if ident == "depth" && fork.peek(Token![=]) {
+        input.parse::<Ident>()?;
+        input.parse::<Token![=]>()?;
+        input
+            .parse::<syn::LitInt>()
+            .map_err(|err| syn::Error::new(err.span(), "expected the depth as an integer literal, like `depth = 2`"))?;
+    } else if ident == "transparent" && fork.peek(syn::token::Paren) {
+        input.parse::<Ident>()?;
+        input.parse::<proc_macro2::Group>()?;
+    }
+    Ok(())
+}
+
+/// `err, Type, { arms }`
+fn whole(input: ParseStream) -> syn::Result<()> {
+    if input.is_empty() {
+        return Err(syn::Error::new(Span::call_site(), "expected `error, Type, { arms }`"));
+    }
+    input.parse::<Expr>()?;
+    comma(input, "expected `,` after the error")?;
+
+    let ty: Type = input.parse()?;
+    match &ty {
+        Type::Path(path) if path.qself.is_none() => {}
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &ty,
+                "expected the path of an enum-like error type, like `Error` or `io::ErrorKind`",
+            ))
+        }
+    }
+    comma(input, "expected `,` after the error type")?;
+
+    if !input.peek(syn::token::Brace) {
+        return Err(input.error("expected the arms in braces, like `{ NotFound => 404, _ => 500 }`"));
+    }
+    let content;
+    braced!(content in input);
+    arms(&content)?;
+
+    input.parse::<Option<Token![,]>>()?;
+    if !input.is_empty() {
+        return Err(input.error("unexpected tokens after the arms"));
+    }
+    Ok(())
+}
+
+/// `Variant(..) if guard => body, .., _ => body`
+fn arms(input: ParseStream) -> syn::Result<()> {
+    while !input.is_empty() {
+        input.call(Attribute::parse_outer)?;
+        let pat = Pat::parse_multi(input)?;
+        let fallback = crate::input::Fallback::of(&pat).is_some();
+        if !fallback {
+            variants(&pat)?;
+        }
+        if input.peek(Token![if]) {
+            let if_token = input.parse::<Token![if]>()?;
+            if fallback {
+                return Err(syn::Error::new(if_token.span, "the fallback arm can't have a guard"));
+            }
+            input.parse::<Expr>()?;
+        }
+        if !input.peek(Token![=>]) {
+            return Err(input.error("expected `=>` after the pattern of the arm"));
+        }
+        input.parse::<Token![=>]>()?;
+        input.parse::<Expr>()?;
+        if input.is_empty() {
+            break;
+        }
+        comma(input, "expected `,` after the arm")?;
+        if fallback && !input.is_empty() {
+            return Err(syn::Error::new_spanned(pat, "the fallback arm must be the last one"));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the pattern names variants of the error type without its path
+fn variants(pat: &Pat) -> syn::Result<()> {
+    let path = match pat {
+        Pat::Or(pat) => return pat.cases.iter().try_for_each(variants),
+        Pat::Ident(pat) if pat.subpat.is_none() => return Ok(()),
+        Pat::Path(pat) => &pat.path,
+        Pat::TupleStruct(pat) => &pat.path,
+        Pat::Struct(pat) => &pat.path,
+        Pat::Wild(_) => return Ok(()),
+        pat => {
+            return Err(syn::Error::new_spanned(
+                pat,
+                "expected a variant of the error type, `_` or `name @ _`",
+            ))
+        }
+    };
+    match (path.get_ident(), path.segments.last()) {
+        (Some(_), _) => Ok(()),
+        (None, Some(variant)) => Err(syn::Error::new_spanned(
+            path,
+            format!(
+                "the error type is prepended to the variants, write `{}` instead of `{}`",
+                variant.ident,
+                path.to_token_stream().to_string().replace(' ', "")
+            ),
+        )),
+        (None, None) => Err(syn::Error::new(path.span(), "expected a variant name")),
+    }
+}
+
+fn comma(input: ParseStream, msg: &str) -> syn::Result<()> {
+    if input.peek(Token![,]) {
+        input.parse::<Token![,]>()?;
+        Ok(())
+    } else {
+        Err(input.error(msg))
+    }
+}
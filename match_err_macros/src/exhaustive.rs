@@ -0,0 +1,12 @@
+use proc_macro2::TokenStream;
+
+use crate::input::{expand_match, split_arms, MatchInput, Unmatched};
+
+pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let MatchInput { krate: crate::input::Krate(krate), any, ty, arms } = syn::parse2(input)?;
+
+    let (variants, fallback) = split_arms(&ty, arms)?;
+    let fallback = fallback.map(|(fallback, _, body)| (fallback, body));
+
+    Ok(expand_match(&krate, &any, &ty, &variants, fallback, Unmatched::Rejected, TokenStream::new()))
+}
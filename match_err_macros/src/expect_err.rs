@@ -0,0 +1,68 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{Expr, ItemFn, Pat, ReturnType, Token};
+
+/// The expected error: a pattern of an enum-like error type with an optional guard
+pub struct Expected {
+    pat: Pat,
+    guard: Option<Expr>,
+}
+
+impl Parse for Expected {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let pat = Pat::parse_multi_with_leading_vert(input)?;
+        let guard = if input.parse::<Option<Token![if]>>()?.is_some() {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Expected { pat, guard })
+    }
+}
+
+pub fn expand(expected: Expected, item: ItemFn) -> syn::Result<TokenStream> {
+    let ItemFn {
+        attrs,
+        vis,
+        mut sig,
+        block,
+    } = item;
+
+    let ReturnType::Type(_, ty) = std::mem::replace(&mut sig.output, ReturnType::Default) else {
+        return Err(syn::Error::new_spanned(
+            &sig.ident,
+            "#[expect_err] can only be used on functions returning a `Result`",
+        ));
+    };
+
+    let result = if sig.asyncness.is_some() {
+        quote!(async move #block.await)
+    } else {
+        quote!((|| -> #ty #block)())
+    };
+
+    let Expected { pat, guard } = expected;
+    let guard = guard.map(|guard| quote!(if #guard));
+    let description = quote!(#pat #guard).to_string();
+    let krate = quote!(::match_err);
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            let result: #ty = #result;
+            match result {
+                ::core::result::Result::Ok(_) => #krate::__private::assert_failed(
+                    "expect_err",
+                    ::core::format_args!(
+                        "assertion failed: expected an error matching `{}`, but the function succeeded",
+                        #description
+                    ),
+                ),
+                ::core::result::Result::Err(err) => {
+                    #krate::assert_err_matches!(err, #pat #guard);
+                }
+            }
+        }
+    })
+}
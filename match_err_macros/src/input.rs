@@ -0,0 +1,213 @@
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{braced, Arm, Expr, Ident, Pat, Path, PathArguments, PathSegment, Token};
+
+/// The path of the `match_err` crate as passed by its macro_rules wrappers, followed by `;`
+pub struct Krate(pub TokenStream);
+
+impl Parse for Krate {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut tokens = TokenStream::new();
+        while !input.peek(Token![;]) {
+            tokens.extend([input.parse::<TokenTree>()?]);
+        }
+        input.parse::<Token![;]>()?;
+        Ok(Krate(tokens))
+    }
+}
+
+/// `$any, Type, { arms }`
+pub struct MatchInput {
+    pub krate: Krate,
+    pub any: Expr,
+    pub ty: Path,
+    pub arms: Vec<Arm>,
+}
+
+impl Parse for MatchInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let krate = input.parse()?;
+        let any = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let ty = input.parse()?;
+        input.parse::<Token![,]>()?;
+
+        let content;
+        braced!(content in input);
+        let mut arms = Vec::new();
+        while !content.is_empty() {
+            arms.push(content.parse()?);
+        }
+        input.parse::<Option<Token![,]>>()?;
+
+        Ok(MatchInput { krate, any, ty, arms })
+    }
+}
+
+/// The arm catching everything that isn't matched by the variant arms
+pub enum Fallback {
+    Wild,
+    Bind(syn::Ident),
+}
+
+impl Fallback {
+    /// Returns the fallback an arm's pattern stands for, if it's `_` or `e @ _`
+    pub fn of(pat: &Pat) -> Option<Fallback> {
+        match pat {
+            Pat::Wild(_) => Some(Fallback::Wild),
+            Pat::Ident(pat) => match &pat.subpat {
+                Some((_, sub)) if matches!(**sub, Pat::Wild(_)) => Some(Fallback::Bind(pat.ident.clone())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// The fallback arm's kind, pattern and body
+pub type FallbackArm = (Fallback, Pat, Box<Expr>);
+
+/// Splits the arms into the variant arms, with their patterns qualified by the error type, and
+/// the fallback arm with its pattern and body
+pub fn split_arms(ty: &Path, arms: Vec<Arm>) -> syn::Result<(Vec<Arm>, Option<FallbackArm>)> {
+    let mut variants = Vec::new();
+    let mut fallback = None;
+    for mut arm in arms {
+        if let Some(found) = Fallback::of(&arm.pat) {
+            if fallback.is_some() {
+                return Err(syn::Error::new(arm.pat.span(), "only one fallback arm is allowed"));
+            }
+            if let Some((if_token, _)) = &arm.guard {
+                return Err(syn::Error::new(if_token.span, "the fallback arm can't have a guard"));
+            }
+            fallback = Some((found, arm.pat, arm.body));
+            continue;
+        }
+        if fallback.is_some() {
+            return Err(syn::Error::new(arm.pat.span(), "the fallback arm must be the last one"));
+        }
+        arm.pat = qualify(ty, arm.pat)?;
+        arm.comma.get_or_insert_with(Default::default);
+        variants.push(arm);
+    }
+    Ok((variants, fallback))
+}
+
+/// What the variants of the error type that none of the variant arms matches run
+pub enum Unmatched {
+    /// The fallback arm, like the errors of other types
+    Fallback,
+    /// Nothing, the variant arms have to be exhaustive
+    Rejected,
+}
+
+/// Expands the match of the downcast error against the variant arms, with the fallback arm for
+/// errors of other types, or `{}` without one. `check` is put before the match
+pub fn expand_match(
+    krate: &TokenStream,
+    any: &Expr,
+    ty: &Path,
+    variants: &[Arm],
+    fallback: Option<(Fallback, Box<Expr>)>,
+    unmatched: Unmatched,
+    check: TokenStream,
+) -> TokenStream {
+    let any_ident = Ident::new("any", Span::mixed_site());
+    let err_ident = Ident::new("err", Span::mixed_site());
+    let otherwise = match fallback {
+        Some((Fallback::Wild, body)) => quote!(#body),
+        Some((Fallback::Bind(bind), body)) => quote!({ let #bind = #any_ident; #body }),
+        None => quote!({}),
+    };
+    let unmatched = match unmatched {
+        Unmatched::Fallback => quote! {
+            #[allow(unreachable_patterns)]
+            _ => #otherwise,
+        },
+        Unmatched::Rejected => quote!(),
+    };
+
+    quote! {
+        match &(#any) {
+            #any_ident => {
+                #check
+                match {
+                    #[allow(unused_imports)]
+                    use #krate::__private::{ViaDowncastable as _, ViaError as _};
+                    (&#krate::__private::Probe(#any_ident)).match_err_downcast::<#ty>()
+                } {
+                    ::core::option::Option::Some(#err_ident) => {
+                        #krate::__match_err_hook!(#err_ident);
+                        match #err_ident {
+                            #(#variants)*
+                            #unmatched
+                        }
+                    },
+                    ::core::option::Option::None => #otherwise,
+                }
+            }
+        }
+    }
+}
+
+/// Prefixes every variant of a (possibly or-) pattern with the path of the error type
+pub fn qualify(ty: &Path, pat: Pat) -> syn::Result<Pat> {
+    let mut prefix = ty.clone();
+    for segment in &mut prefix.segments {
+        if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+            args.colon2_token.get_or_insert_with(Default::default);
+        }
+    }
+
+    let qualified = |path: &Path| -> syn::Result<Path> {
+        match path.get_ident() {
+            Some(variant) => {
+                let mut path = prefix.clone();
+                path.segments.push(PathSegment::from(variant.clone()));
+                Ok(path)
+            }
+            None => Err(syn::Error::new(
+                path.span(),
+                "expected a variant name, the error type is prepended to it",
+            )),
+        }
+    };
+
+    Ok(match pat {
+        Pat::Or(mut pat) => {
+            pat.cases = pat
+                .cases
+                .into_iter()
+                .map(|case| qualify(ty, case))
+                .collect::<syn::Result<_>>()?;
+            Pat::Or(pat)
+        }
+        Pat::Ident(pat) if pat.by_ref.is_none() && pat.mutability.is_none() && pat.subpat.is_none() => {
+            Pat::Path(syn::ExprPath {
+                attrs: pat.attrs,
+                qself: None,
+                path: qualified(&Path::from(pat.ident))?,
+            })
+        }
+        Pat::Path(mut pat) if pat.qself.is_none() => {
+            pat.path = qualified(&pat.path)?;
+            Pat::Path(pat)
+        }
+        Pat::TupleStruct(mut pat) if pat.qself.is_none() => {
+            pat.path = qualified(&pat.path)?;
+            Pat::TupleStruct(pat)
+        }
+        Pat::Struct(mut pat) if pat.qself.is_none() => {
+            pat.path = qualified(&pat.path)?;
+            Pat::Struct(pat)
+        }
+        pat => {
+            return Err(syn::Error::new(
+                pat.span(),
+                "expected a variant of the error type, `_` or `name @ _`",
+            ))
+        }
+    })
+}
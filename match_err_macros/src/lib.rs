@@ -0,0 +1,94 @@
+//! Procedural macros for [match_err](https://docs.rs/match_err). Use them through the re-exports
+//! of the `match_err` crate, the macros here expect the path of that crate as the first argument
+
+use proc_macro::TokenStream;
+
+mod arbitrary;
+mod classify;
+mod code;
+mod derive;
+mod diagnose;
+mod exhaustive;
+mod expect_err;
+mod input;
+mod spanned;
+mod strict;
+mod test_variants;
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __match_err_exhaustive(input: TokenStream) -> TokenStream {
+    exhaustive::expand(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __match_err_diagnose(input: TokenStream) -> TokenStream {
+    diagnose::expand(input.into()).into_compile_error().into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __match_err_spanned(input: TokenStream) -> TokenStream {
+    spanned::expand(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __match_err_strict(input: TokenStream) -> TokenStream {
+    strict::expand(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __test_error_variants(input: TokenStream) -> TokenStream {
+    test_variants::expand(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[doc(hidden)]
+#[proc_macro_derive(ArbitraryErr, attributes(proptest))]
+pub fn derive_arbitrary_err(input: TokenStream) -> TokenStream {
+    arbitrary::expand(syn::parse_macro_input!(input))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[doc(hidden)]
+#[proc_macro_derive(Classify, attributes(class))]
+pub fn derive_classify(input: TokenStream) -> TokenStream {
+    classify::expand(syn::parse_macro_input!(input))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[doc(hidden)]
+#[proc_macro_derive(ErrorCode, attributes(code))]
+pub fn derive_error_code(input: TokenStream) -> TokenStream {
+    code::expand(syn::parse_macro_input!(input))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[doc(hidden)]
+#[proc_macro_derive(MatchErr)]
+pub fn derive_match_err(input: TokenStream) -> TokenStream {
+    derive::expand(syn::parse_macro_input!(input))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[doc(hidden)]
+#[proc_macro_attribute]
+pub fn expect_err(attr: TokenStream, item: TokenStream) -> TokenStream {
+    expect_err::expand(syn::parse_macro_input!(attr), syn::parse_macro_input!(item))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,65 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Arm, Ident, Pat};
+
+use crate::input::{expand_match, split_arms, MatchInput, Unmatched};
+
+pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let MatchInput { krate: crate::input::Krate(krate), any, ty, arms } = syn::parse2(input)?;
+
+    let (variants, fallback) = split_arms(&ty, arms)?;
+    let Some((fallback, wild, body)) = fallback else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "match_err_strict! needs a `_` arm for errors of other types",
+        ));
+    };
+
+    let listed: Vec<String> = variants.iter().flat_map(listed_variants).map(|variant| variant.to_string()).collect();
+    let note = format!(
+        "`_` swallows variants of `{}` that aren't listed, list them or add `#[allow(deprecated)]`",
+        quote!(#ty).to_string().replace(' ', "")
+    );
+    let check = quote_spanned!(wild.span()=> check);
+    let check = quote! {
+        {
+            struct Strict<const COVERED: bool>;
+            #[allow(dead_code)]
+            impl Strict<true> {
+                fn check() {}
+            }
+            #[allow(dead_code)]
+            impl Strict<false> {
+                #[deprecated(note = #note)]
+                fn check() {}
+            }
+            Strict::<{ #krate::__private::covers(<#ty as #krate::__private::Variants>::VARIANTS, &[#(#listed),*]) }>::#check();
+        }
+    };
+
+    Ok(expand_match(&krate, &any, &ty, &variants, Some((fallback, body)), Unmatched::Fallback, check))
+}
+
+/// Returns the variants the pattern of an arm starts with
+fn listed_variants(arm: &Arm) -> Vec<&Ident> {
+    fn collect<'a>(pat: &'a Pat, variants: &mut Vec<&'a Ident>) {
+        let path = match pat {
+            Pat::Or(pat) => {
+                for case in &pat.cases {
+                    collect(case, variants);
+                }
+                return;
+            }
+            Pat::Path(pat) => &pat.path,
+            Pat::TupleStruct(pat) => &pat.path,
+            Pat::Struct(pat) => &pat.path,
+            _ => return,
+        };
+        variants.extend(path.segments.last().map(|segment| &segment.ident));
+    }
+
+    let mut variants = Vec::new();
+    collect(&arm.pat, &mut variants);
+    variants
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use proc_macro2::{Group, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{braced, parenthesized, Expr, Ident, Path, Token, Type};
+
+use crate::derive::snake_case;
+use crate::input::Krate;
+
+/// `function, Type, { (args) => Variant(..), .. }`
+pub struct Cases {
+    krate: Krate,
+    function: Path,
+    ty: Type,
+    cases: Vec<Case>,
+}
+
+/// `(args) => Variant`, `(args) => Variant(payload)` or `(args) => Variant { fields }`
+struct Case {
+    args: Punctuated<Expr, Token![,]>,
+    variant: Ident,
+    payload: Option<Group>,
+}
+
+impl Parse for Cases {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let krate = input.parse()?;
+        let function = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let ty = input.parse()?;
+        input.parse::<Token![,]>()?;
+
+        let content;
+        braced!(content in input);
+        let mut cases = Vec::new();
+        while !content.is_empty() {
+            let args;
+            parenthesized!(args in content);
+            let args = Punctuated::parse_terminated(&args)?;
+            content.parse::<Token![=>]>()?;
+            let variant = content.parse()?;
+            let payload = if content.peek(syn::token::Paren) || content.peek(syn::token::Brace) {
+                Some(content.parse()?)
+            } else {
+                None
+            };
+            cases.push(Case { args, variant, payload });
+            if content.is_empty() {
+                break;
+            }
+            content.parse::<Token![,]>()?;
+        }
+        input.parse::<Option<Token![,]>>()?;
+
+        Ok(Cases { krate, function, ty, cases })
+    }
+}
+
+pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let Cases { krate: Krate(krate), function, ty, cases } = syn::parse2(input)?;
+    let prefix = match function.segments.last() {
+        Some(segment) => segment.ident.to_string(),
+        None => return Err(syn::Error::new_spanned(&function, "expected the path of a function")),
+    };
+
+    let mut seen = HashMap::new();
+    let mut tests = Vec::new();
+    for Case { args, variant, payload } in cases {
+        let name = format!("{prefix}_{}", snake_case(&variant));
+        let count = seen.entry(name.clone()).and_modify(|count| *count += 1).or_insert(1usize);
+        let name = match *count {
+            1 => format_ident!("{}", name, span = variant.span()),
+            n => format_ident!("{}_{}", name, n, span = variant.span()),
+        };
+        let args = args.iter();
+        tests.push(quote! {
+            #[test]
+            fn #name() {
+                let err = match #function(#(#args),*) {
+                    ::core::result::Result::Ok(value) => #krate::__private::assert_failed(
+                        "test_error_variants",
+                        ::core::format_args!("assertion failed: expected an error, got Ok({:?})", value),
+                    ),
+                    ::core::result::Result::Err(err) => err,
+                };
+                #krate::assert_error!(err, #ty, #variant #payload);
+            }
+        });
+    }
+    Ok(quote!(#(#tests)*))
+}
@@ -0,0 +1,393 @@
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec, vec::Vec};
+use core::error::Error;
+use core::fmt::{self, Display};
+
+#[cfg(feature = "std")]
+use crate::report::Value;
+
+/// The way an error differs from the expected one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The error isn't of the expected type
+    Type,
+    /// The error is of the expected type but another variant
+    Variant,
+    /// The error is the expected variant with another payload
+    Payload,
+}
+
+impl Mismatch {
+    #[doc(hidden)]
+    pub fn of<T: PartialEq>(actual: &T, expected: &T) -> Option<Mismatch> {
+        if actual == expected {
+            None
+        } else if core::mem::discriminant(actual) == core::mem::discriminant(expected) {
+            Some(Mismatch::Payload)
+        } else {
+            Some(Mismatch::Variant)
+        }
+    }
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Mismatch::Type => "wrong type",
+            Mismatch::Variant => "wrong variant",
+            Mismatch::Payload => "wrong payload",
+        })
+    }
+}
+
+/// A structured description of how an error differs from the expected one, returned by
+/// [`check_error!`](crate::check_error). Requires the `alloc` feature
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorMismatch {
+    kind: Mismatch,
+    expected: String,
+    actual: Option<String>,
+    type_name: &'static str,
+    error: String,
+    chain: Vec<String>,
+    #[cfg(feature = "pretty")]
+    diff: Option<(String, String)>,
+}
+
+#[cfg(feature = "alloc")]
+impl ErrorMismatch {
+    #[doc(hidden)]
+    pub fn new<E: Display + ?Sized>(
+        kind: Mismatch,
+        expected: &dyn fmt::Debug,
+        actual: Option<&dyn fmt::Debug>,
+        err: &E,
+        source: Option<&(dyn Error + 'static)>,
+    ) -> Self {
+        let chain = match source {
+            Some(err) => {
+                let mut chain = Vec::new();
+                let mut layer = Some(err);
+                while let Some(err) = layer {
+                    chain.push(format!("{err}"));
+                    layer = err.source();
+                }
+                chain
+            }
+            None => vec![format!("{err}")],
+        };
+        ErrorMismatch {
+            kind,
+            expected: format!("{expected:?}"),
+            actual: actual.map(|actual| format!("{actual:?}")),
+            type_name: core::any::type_name::<E>(),
+            error: format!("{err:#}"),
+            chain,
+            #[cfg(feature = "pretty")]
+            diff: actual
+                .filter(|_| kind == Mismatch::Payload)
+                .map(|actual| (format!("{expected:#?}"), format!("{actual:#?}"))),
+        }
+    }
+
+    /// Returns the way the error differs from the expected one
+    pub fn kind(&self) -> Mismatch {
+        self.kind
+    }
+
+    /// Returns the `Debug` rendering of the expected error
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// Returns the `Debug` rendering of the error when it's of the expected type
+    pub fn actual(&self) -> Option<&str> {
+        self.actual.as_deref()
+    }
+
+    /// Returns the type name of the checked value
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the `{:#}` rendering of the checked value
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+
+    /// Returns the `Display` rendering of every layer of the cause chain
+    pub fn chain(&self) -> &[String] {
+        &self.chain
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Display for ErrorMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\n expected: {}\n   actual: ", self.kind, self.expected)?;
+        match &self.actual {
+            Some(actual) => write!(f, "Some({actual})")?,
+            None => f.write_str("None")?,
+        }
+        write!(f, "\n     type: {}\n    error: {}", self.type_name, self.error)?;
+        #[cfg(feature = "pretty")]
+        if let Some((expected, actual)) = &self.diff {
+            write!(f, "\n     diff: - expected / + actual\n{}", crate::diff::Diff { expected, actual })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ErrorMismatch {}
+
+/// Collects failed error checks of a test, to fail once with all of them instead of at the first
+/// one. Add checks with [`soft_assert_error!`](crate::soft_assert_error) or with
+/// [`check`](ErrorAssertions::check) and the result of [`check_error!`](crate::check_error) or
+/// [`verify_error!`](crate::verify_error), then call [`verify`](ErrorAssertions::verify). With the
+/// `std` feature, dropping the session with failures that weren't verified panics as well, unless
+/// the thread is already panicking. Without `std` that can't be told, so dropping doesn't panic and
+/// `verify` is the only way to fail. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::panic::catch_unwind;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let mut session = ErrorAssertions::new();
+///  soft_assert_error!(session, anyhow!(Error::NotFound), Error, NotFound);
+///  soft_assert_error!(session, anyhow!(Error::NotFound), Error, Custom(String::from("a")));
+///  session.check(verify_error!(anyhow!("unknown"), Error, Custom(_)));
+///  assert_eq!(session.failures().count(), 2);
+///
+///  let msg = *catch_unwind(move || session.verify()).unwrap_err().downcast::<String>().unwrap();
+///  assert!(msg.starts_with("assertion failed: 2 of 3 error checks failed\n[1] "));
+///  assert!(msg.contains(": wrong variant\n expected: Custom(\"a\")"));
+///  assert!(msg.contains(": wrong type\n expected: Custom(_)"));
+///
+///  let mut session = ErrorAssertions::new();
+///  soft_assert_error!(session, anyhow!(Error::NotFound), Error, NotFound);
+///  session.verify();
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Default)]
+pub struct ErrorAssertions {
+    checks: usize,
+    failures: Vec<(ErrorMismatch, &'static core::panic::Location<'static>)>,
+}
+
+#[cfg(feature = "alloc")]
+impl ErrorAssertions {
+    /// Creates a session without checks
+    pub fn new() -> Self {
+        ErrorAssertions::default()
+    }
+
+    /// Records the result of a check at the caller's location, returns `true` if it passed
+    #[track_caller]
+    pub fn check(&mut self, res: Result<(), ErrorMismatch>) -> bool {
+        self.checks += 1;
+        match res {
+            Ok(()) => true,
+            Err(mismatch) => {
+                self.failures.push((mismatch, core::panic::Location::caller()));
+                false
+            }
+        }
+    }
+
+    /// Iterates over the failed checks in the order they were made
+    pub fn failures(&self) -> impl Iterator<Item = &ErrorMismatch> {
+        self.failures.iter().map(|(mismatch, _)| mismatch)
+    }
+
+    /// Panics with all failed checks if there are any
+    #[track_caller]
+    pub fn verify(mut self) {
+        let failures = core::mem::take(&mut self.failures);
+        if !failures.is_empty() {
+            panic!("assertion failed: {}", Failures { checks: self.checks, failures: &failures });
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for ErrorAssertions {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        if !self.failures.is_empty() {
+            panic!(
+                "ErrorAssertions dropped without verify(): {}",
+                Failures { checks: self.checks, failures: &self.failures }
+            );
+        }
+    }
+}
+
+/// Renders the failed checks of an [`ErrorAssertions`] session with their locations
+#[cfg(feature = "alloc")]
+struct Failures<'a> {
+    checks: usize,
+    failures: &'a [(ErrorMismatch, &'static core::panic::Location<'static>)],
+}
+
+#[cfg(feature = "alloc")]
+impl Display for Failures<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of {} error checks failed", self.failures.len(), self.checks)?;
+        for (i, (mismatch, location)) in self.failures.iter().enumerate() {
+            write!(f, "\n[{}] {location}: {mismatch}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders the layers of an error chain as `layer -> layer -> ...`
+struct Chain<'a>(Option<&'a (dyn Error + 'static)>);
+
+impl Display for Chain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut layer = self.0;
+        let mut first = true;
+        while let Some(err) = layer {
+            if !first {
+                f.write_str(" -> ")?;
+            }
+            write!(f, "{err:?}")?;
+            first = false;
+            layer = err.source();
+        }
+        Ok(())
+    }
+}
+
+/// Calls the comparator of `assert_error!(.., with cmp)`, so the types of its arguments are known
+/// when the closure is checked
+#[doc(hidden)]
+pub fn compare_with<T: ?Sized>(actual: &T, expected: &T, cmp: impl FnOnce(&T, &T) -> bool) -> bool {
+    cmp(actual, expected)
+}
+
+/// Float payloads of `assert_error_approx!`, equal if every number is within epsilon of the other
+#[doc(hidden)]
+pub trait ApproxEq {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool;
+}
+
+impl ApproxEq for f64 {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        // `f64::abs` isn't in core before 1.85
+        let diff = if self > other { self - other } else { other - self };
+        diff <= epsilon
+    }
+}
+
+impl ApproxEq for f32 {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        f64::from(*self).approx_eq(&f64::from(*other), epsilon)
+    }
+}
+
+impl<A: ApproxEq, B: ApproxEq> ApproxEq for (A, B) {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.0.approx_eq(&other.0, epsilon) && self.1.approx_eq(&other.1, epsilon)
+    }
+}
+
+impl<T: ApproxEq, const N: usize> ApproxEq for [T; N] {
+    fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.iter().zip(other).all(|(a, b)| a.approx_eq(b, epsilon))
+    }
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_failed(assertion: &str, msg: fmt::Arguments<'_>) -> ! {
+    #[cfg(not(feature = "std"))]
+    let _ = assertion;
+    #[cfg(feature = "std")]
+    crate::report::report(
+        assertion,
+        &[("message", Value::Str(&format!("{msg}")))],
+        core::panic::Location::caller(),
+    );
+    panic!("{msg}")
+}
+
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_error_failed(mismatch: &ErrorMismatch, msg: Option<fmt::Arguments<'_>>) -> ! {
+    #[cfg(feature = "std")]
+    {
+        let msg = msg.map(|msg| format!("{msg}"));
+        crate::report::report(
+            "assert_error",
+            &[
+                ("kind", Value::Str(&format!("{}", mismatch.kind))),
+                ("message", Value::Opt(msg.as_deref())),
+                ("expected", Value::Str(&mismatch.expected)),
+                ("actual", Value::Opt(mismatch.actual.as_deref())),
+                ("type", Value::Str(mismatch.type_name)),
+                ("error", Value::Str(&mismatch.error)),
+                ("chain", Value::List(&mismatch.chain)),
+            ],
+            core::panic::Location::caller(),
+        );
+    }
+    match msg {
+        Some(msg) => panic!("assertion failed: {msg}: {mismatch}"),
+        None => panic!("assertion failed: {mismatch}"),
+    }
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_chain_failed(
+    depth: usize,
+    expected: &str,
+    layer: Option<&(dyn Error + 'static)>,
+    root: Option<&(dyn Error + 'static)>,
+) -> ! {
+    #[cfg(feature = "std")]
+    {
+        let mut chain = Vec::new();
+        let mut next = root;
+        while let Some(err) = next {
+            chain.push(format!("{err:?}"));
+            next = err.source();
+        }
+        crate::report::report(
+            "assert_error_chain",
+            &[
+                ("depth", Value::Num(depth)),
+                ("expected", Value::Str(expected)),
+                ("layer", Value::Opt(layer.map(|layer| format!("{layer:?}")).as_deref())),
+                ("chain", Value::List(&chain)),
+            ],
+            core::panic::Location::caller(),
+        );
+    }
+    match layer {
+        Some(layer) => panic!(
+            "assertion failed: layer {depth} of the error chain doesn't match `{expected}`\n    layer: {layer:?}\n    chain: {}",
+            Chain(root)
+        ),
+        None => panic!(
+            "assertion failed: the error chain ends before layer {depth}, expected `{expected}`\n    chain: {}",
+            Chain(root)
+        ),
+    }
+}
@@ -0,0 +1,216 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::error::Error;
+use core::iter::FusedIterator;
+
+use crate::downcast::downcast_layer;
+use crate::Downcastable;
+
+/// Iterator over an error and its `source()` chain, starting with the error itself
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  #[error("upstream failed")]
+///  struct Upstream(#[source] Error);
+///
+///  let err = anyhow!(Upstream(Error::NotFound)).context("loading user");
+///  let messages: Vec<_> = ErrorChainIter::of(&err).map(|e| e.to_string()).collect();
+///  assert_eq!(messages, ["loading user", "upstream failed", "not found"]);
+///
+///  let err = Upstream(Error::NotFound);
+///  assert_eq!(ErrorChainIter::new(&err).count(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ErrorChainIter<'a> {
+    next: Option<&'a (dyn Error + 'static)>,
+}
+
+impl<'a> ErrorChainIter<'a> {
+    /// Iterates over the error and its sources
+    pub fn new(err: &'a (dyn Error + 'static)) -> Self {
+        ErrorChainIter { next: Some(err) }
+    }
+
+    /// Iterates over the chain of any [`Downcastable`] error. Empty for types that don't expose
+    /// their error as a trait object
+    pub fn of<D: Downcastable + ?Sized>(err: &'a D) -> Self {
+        ErrorChainIter { next: err.to_dyn_error() }
+    }
+}
+
+impl<'a> Iterator for ErrorChainIter<'a> {
+    type Item = &'a (dyn Error + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let err = self.next?;
+        self.next = err.source();
+        Some(err)
+    }
+}
+
+impl FusedIterator for ErrorChainIter<'_> {}
+
+/// Iterates over the errors of type `T` in the cause chain, outermost first. The layers are
+/// downcast like [`Downcastable::find_in_chain`] does, and what it finds outside of the `source()`
+/// chain, like a report nested in an anyhow error or a frame of an error-stack report, comes first
+fn matches_in_chain<T, D>(err: &D) -> impl Iterator<Item = &T>
+where
+    T: Error + Send + Sync + 'static,
+    D: Downcastable + ?Sized,
+{
+    let layers = ErrorChainIter::of(err).filter_map(downcast_layer::<T>);
+    let outside = err
+        .find_in_chain::<T>()
+        .filter(|found| !layers.clone().any(|layer| core::ptr::eq(*found, layer)));
+    outside.into_iter().chain(layers)
+}
+
+/// Returns the first error of type `T` in the cause chain. A plain function counterpart of
+/// [`match_err_chain!`](crate::match_err_chain) for generic code
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::io;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  #[error("read failed")]
+///  struct ReadError(#[source] io::Error);
+///
+///  fn is_disk_full(err: &(dyn std::error::Error + 'static)) -> bool {
+///     find_in_chain::<io::Error>(err).is_some_and(|e| e.to_string() == "disk full")
+///  }
+///
+///  assert!(is_disk_full(&ReadError(io::Error::other("disk full"))));
+///  assert!(find_in_chain::<io::Error>(&anyhow!(ReadError(io::Error::other("disk")))).is_some());
+///  assert!(find_in_chain::<io::Error>(&anyhow!("unknown")).is_none());
+///
+///  #[derive(thiserror::Error, Debug)]
+///  #[error("shared read failed")]
+///  struct SharedReadError(#[source] std::sync::Arc<io::Error>);
+///
+///  let err = anyhow!(SharedReadError(std::sync::Arc::new(io::Error::other("disk full"))));
+///  assert!(is_caused_by::<io::Error>(&err));
+///  assert!(is_disk_full(err.as_ref()));
+///  assert_eq!(collect_in_chain::<io::Error>(&err).len(), 1);
+/// ```
+///
+/// The chain is searched the way of [`Downcastable::find_in_chain`], so the functions agree with the
+/// macros on types that have no `source()` chain, like the reports of `error-stack`
+///
+/// ```
+///  # #[cfg(feature = "error-stack")] {
+///  use match_err::*;
+///  use std::io;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  #[error("read failed")]
+///  struct ReadError;
+///
+///  let report = error_stack::Report::new(io::Error::other("disk full")).change_context(ReadError);
+///  assert_eq!(find_in_chain::<io::Error>(&report).unwrap().to_string(), "disk full");
+///  assert!(is_caused_by::<ReadError>(&report));
+///  assert_eq!(collect_in_chain::<io::Error>(&report).len(), 1);
+///  assert!(caused_by_variant(&report, |e: &io::Error| e.kind() == io::ErrorKind::Other));
+///  # }
+/// ```
+pub fn find_in_chain<T: Error + Send + Sync + 'static>(err: &(impl Downcastable + ?Sized)) -> Option<&T> {
+    err.find_in_chain::<T>()
+}
+
+/// Returns all errors of type `T` in the cause chain, outermost first. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(Debug)]
+///  struct Attempt(u32, Option<Box<Attempt>>);
+///
+///  impl std::fmt::Display for Attempt {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "attempt {} failed", self.0)
+///     }
+///  }
+///
+///  impl std::error::Error for Attempt {
+///     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+///         self.1.as_deref().map(|e| e as _)
+///     }
+///  }
+///
+///  let err = anyhow!(Attempt(2, Some(Box::new(Attempt(1, None))))).context("syncing");
+///  let attempts: Vec<_> = collect_in_chain::<Attempt>(&err).into_iter().map(|a| a.0).collect();
+///  assert_eq!(attempts, [2, 1]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn collect_in_chain<T: Error + Send + Sync + 'static>(err: &(impl Downcastable + ?Sized)) -> Vec<&T> {
+    matches_in_chain::<T, _>(err).collect()
+}
+
+/// Returns `true` if an error of type `T` is anywhere in the cause chain. Handy in `filter` and
+/// `retain` closures where a macro doesn't fit
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::{anyhow, Context};
+///  use std::io;
+///
+///  let res: Result<(), _> = Err(io::Error::other("disk full"));
+///  let errors = vec![res.context("writing cache").unwrap_err(), anyhow!("unknown")];
+///
+///  let io_errors: Vec<_> = errors.iter().filter(|e| is_caused_by::<io::Error>(*e)).collect();
+///  assert_eq!(io_errors.len(), 1);
+///
+///  let nested = anyhow::Error::msg(anyhow!(io::Error::other("disk full")));
+///  assert!(is_caused_by::<io::Error>(&nested));
+///  assert_eq!(find_in_chain::<io::Error>(&nested).unwrap().to_string(), "disk full");
+///  assert_eq!(collect_in_chain::<io::Error>(&nested).len(), 1);
+///  assert!(caused_by_variant(&nested, |e: &io::Error| e.to_string() == "disk full"));
+/// ```
+pub fn is_caused_by<T: Error + Send + Sync + 'static>(err: &(impl Downcastable + ?Sized)) -> bool {
+    find_in_chain::<T>(err).is_some()
+}
+
+/// Returns `true` if an error of type `T` in the cause chain satisfies the predicate, the type is
+/// inferred from the closure
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("timeout")]
+///     Timeout,
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let mut errors = vec![anyhow!(Error::Timeout), anyhow!(Error::NotFound), anyhow!("unknown")];
+///  errors.retain(|e| !caused_by_variant(e, |e: &Error| matches!(e, Error::Timeout)));
+///
+///  assert_eq!(errors.len(), 2);
+///  assert!(!caused_by_variant(&errors[0], |e: &Error| matches!(e, Error::Timeout)));
+/// ```
+pub fn caused_by_variant<T, F>(err: &(impl Downcastable + ?Sized), predicate: F) -> bool
+where
+    T: Error + Send + Sync + 'static,
+    F: Fn(&T) -> bool,
+{
+    matches_in_chain::<T, _>(err).any(predicate)
+}
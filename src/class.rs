@@ -0,0 +1,110 @@
+/// Whether retrying an operation that failed with an error can succeed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ErrorClass {
+    /// The failure is temporary and the operation can be retried, like a timeout
+    Transient,
+    /// Retrying fails the same way, like invalid input
+    Permanent,
+    /// Nothing is known about the failure
+    #[default]
+    Unknown,
+}
+
+impl ErrorClass {
+    /// Returns `true` if the error is [`ErrorClass::Transient`]
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ErrorClass::Transient)
+    }
+
+    /// Returns `true` if the error is [`ErrorClass::Permanent`]
+    pub fn is_permanent(&self) -> bool {
+        matches!(self, ErrorClass::Permanent)
+    }
+}
+
+/// Error types that know their [`ErrorClass`]. Usually derived, see [`Classify`](derive@crate::Classify)
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///
+///  struct Timeout;
+///
+///  impl Classify for Timeout {
+///     fn class(&self) -> ErrorClass {
+///         ErrorClass::Transient
+///     }
+///  }
+///
+///  assert!(Timeout.class().is_transient());
+/// ```
+pub trait Classify {
+    /// Returns the class of the error
+    fn class(&self) -> ErrorClass;
+}
+
+impl<C: Classify + ?Sized> Classify for &C {
+    fn class(&self) -> ErrorClass {
+        (**self).class()
+    }
+}
+
+/// Classifies an error by the first of the listed types found anywhere in the cause chain, trying
+/// the types in the given order. The types have to implement [`Classify`]. Returns
+/// [`ErrorClass::Unknown`] if none of them is in the chain
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "macros")]
+/// # {
+///  use match_err::*;
+///  use anyhow::{anyhow, Context};
+///
+///  #[derive(thiserror::Error, Debug, Classify)]
+///  enum DbError {
+///     #[error("pool timed out")]
+///     #[class(transient)]
+///     PoolTimeout,
+///     #[error("constraint {0} violated")]
+///     #[class(permanent)]
+///     Constraint(String),
+///     #[error("other")]
+///     Other,
+///  }
+///
+///  #[derive(thiserror::Error, Debug, Classify)]
+///  #[class(permanent)]
+///  enum RequestError {
+///     #[error("bad request")]
+///     BadRequest,
+///     #[error("unavailable")]
+///     #[class(transient)]
+///     Unavailable,
+///  }
+///
+///  let res: Result<(), _> = Err(DbError::PoolTimeout);
+///  let err = res.context("loading user").unwrap_err();
+///  assert_eq!(classify_err!(err, DbError, RequestError), ErrorClass::Transient);
+///
+///  assert_eq!(classify_err!(anyhow!(DbError::Constraint("email".into())), DbError), ErrorClass::Permanent);
+///  assert_eq!(classify_err!(anyhow!(DbError::Other), DbError), ErrorClass::Unknown);
+///  assert_eq!(classify_err!(anyhow!(RequestError::BadRequest), DbError, RequestError), ErrorClass::Permanent);
+///  assert!(classify_err!(anyhow!(RequestError::Unavailable), DbError, RequestError).is_transient());
+///  assert_eq!(classify_err!(anyhow!("unknown"), DbError, RequestError), ErrorClass::Unknown);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! classify_err {
+    ( $any:expr, $( $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)? ),+ $(,)? ) => (
+        match &$any {
+            any => 'classified: {
+                $(
+                    if let Some(e) = $crate::__match_err_probe!(match_err_find any, $($ty)::+ $(::<$($gen),+>)?) {
+                        break 'classified $crate::Classify::class(e);
+                    }
+                )+
+                $crate::ErrorClass::Unknown
+            }
+        }
+    );
+}
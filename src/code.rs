@@ -0,0 +1,118 @@
+use core::fmt::{self, Display};
+
+/// A stable code identifying an error for clients and log queries, which keeps its value when the
+/// variants of the error are renamed or reordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Code {
+    /// A numeric code, like `1001`
+    Num(u32),
+    /// A string code, like `"USER_NOT_FOUND"`
+    Str(&'static str),
+}
+
+impl Code {
+    /// Returns the number of a numeric code
+    pub fn as_num(&self) -> Option<u32> {
+        match self {
+            Code::Num(num) => Some(*num),
+            Code::Str(_) => None,
+        }
+    }
+
+    /// Returns the string of a string code
+    pub fn as_str(&self) -> Option<&'static str> {
+        match self {
+            Code::Num(_) => None,
+            Code::Str(s) => Some(s),
+        }
+    }
+}
+
+impl Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Code::Num(num) => write!(f, "{num}"),
+            Code::Str(s) => f.write_str(s),
+        }
+    }
+}
+
+/// Error types that know their stable [`Code`]. Usually derived, see [`ErrorCode`](derive@crate::ErrorCode)
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///
+///  struct Timeout;
+///
+///  impl ErrorCode for Timeout {
+///     fn code(&self) -> Code {
+///         Code::Num(504)
+///     }
+///  }
+///
+///  assert_eq!(Timeout.code().as_num(), Some(504));
+/// ```
+pub trait ErrorCode {
+    /// Returns the code of the error
+    fn code(&self) -> Code;
+}
+
+impl<C: ErrorCode + ?Sized> ErrorCode for &C {
+    fn code(&self) -> Code {
+        (**self).code()
+    }
+}
+
+/// Returns the [`Code`] of the first of the listed types found anywhere in the cause chain, trying
+/// the types in the given order. The types have to implement [`ErrorCode`]. Returns `None` if
+/// none of them is in the chain
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "macros")]
+/// # {
+///  use match_err::*;
+///  use anyhow::{anyhow, Context};
+///
+///  #[derive(thiserror::Error, Debug, ErrorCode)]
+///  enum DbError {
+///     #[error("user not found")]
+///     #[code("USER_NOT_FOUND")]
+///     NotFound,
+///     #[error("constraint {0} violated")]
+///     #[code("CONSTRAINT")]
+///     Constraint(String),
+///  }
+///
+///  #[derive(thiserror::Error, Debug, ErrorCode)]
+///  enum RequestError {
+///     #[error("bad request")]
+///     #[code(4000)]
+///     BadRequest,
+///  }
+///
+///  let res: Result<(), _> = Err(DbError::NotFound);
+///  let err = res.context("loading user").unwrap_err();
+///  assert_eq!(code_of!(err, RequestError, DbError), Some(Code::Str("USER_NOT_FOUND")));
+///
+///  assert_eq!(code_of!(anyhow!(RequestError::BadRequest), DbError, RequestError), Some(Code::Num(4000)));
+///  assert_eq!(code_of!(anyhow!("unknown"), DbError, RequestError), None);
+///  assert_eq!(code_of!(err, DbError).unwrap().to_string(), "USER_NOT_FOUND");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! code_of {
+    ( $any:expr, $( $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)? ),+ $(,)? ) => (
+        match &$any {
+            any => 'coded: {
+                $(
+                    if let Some(e) = $crate::__match_err_probe!(match_err_find any, $($ty)::+ $(::<$($gen),+>)?) {
+                        break 'coded ::core::option::Option::Some($crate::ErrorCode::code(e));
+                    }
+                )+
+                ::core::option::Option::None
+            }
+        }
+    );
+}
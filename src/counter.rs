@@ -0,0 +1,85 @@
+/// Increments a counter of the `metrics` facade for an error of an enum-like error type and
+/// returns the result unchanged. The counter is labeled with the names of the type and variant,
+/// and named `errors_total` unless a name is given. Errors of other types aren't counted. The
+/// variant name comes from [`VariantName`](crate::VariantName), types that don't implement it fall
+/// back to their `Debug` rendering, which formats the payload and has to be derived.
+/// Requires the `metrics` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "metrics")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("code {0}")]
+///     Code(u16),
+///  }
+///
+///  let counted = capture(|| {
+///     let res: anyhow::Result<()> = count_err_variant!(Err(anyhow!(Error::Code(503))), Error);
+///     assert!(res.is_err());
+///     let _ = count_err_variant!(Err::<(), _>(anyhow!(Error::NotFound)), Error, "api_errors");
+///     let _ = count_err_variant!(Err::<(), _>(anyhow!("unknown")), Error);
+///     let _ = count_err_variant!(Ok::<_, anyhow::Error>(1), Error);
+///  });
+///  assert_eq!(counted, [
+///     "errors_total type=Error variant=Code",
+///     "api_errors type=Error variant=NotFound",
+///  ]);
+///
+///  # fn capture(f: impl FnOnce()) -> Vec<String> {
+///  #     use metrics::{Counter, CounterFn, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+///  #     use std::sync::{Arc, Mutex};
+///  #     struct Increment(Arc<Mutex<Vec<String>>>, String);
+///  #     impl CounterFn for Increment {
+///  #         fn increment(&self, _: u64) { self.0.lock().unwrap().push(self.1.clone()) }
+///  #         fn absolute(&self, _: u64) {}
+///  #     }
+///  #     struct Capture(Arc<Mutex<Vec<String>>>);
+///  #     impl Recorder for Capture {
+///  #         fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+///  #         fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+///  #         fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+///  #         fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+///  #             let labels: Vec<_> = key.labels().map(|l| format!("{}={}", l.key(), l.value())).collect();
+///  #             Counter::from_arc(Arc::new(Increment(self.0.clone(), format!("{} {}", key.name(), labels.join(" ")))))
+///  #         }
+///  #         fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge { Gauge::noop() }
+///  #         fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram { Histogram::noop() }
+///  #     }
+///  #     let counted = Arc::new(Mutex::new(Vec::new()));
+///  #     metrics::with_local_recorder(&Capture(counted.clone()), f);
+///  #     let counted = counted.lock().unwrap().clone();
+///  #     counted
+///  # }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! count_err_variant {
+    ( $res:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)? $(,)? ) => (
+        $crate::count_err_variant!($res, $($ty)::+ $(::<$($gen),+>)?, "errors_total")
+    );
+
+    ( $res:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, $name:expr $(,)? ) => (
+        match $res {
+            result => {
+                if let Err(err) = &result {
+                    if let Some(e) = $crate::__match_err_probe!(match_err_downcast err, $($ty)::+ $(::<$($gen),+>)?) {
+                        $crate::__private::metrics::counter!(
+                            $name,
+                            "type" => $crate::__private::type_name::<$($ty)::+ $(::<$($gen),+>)?>(),
+                            "variant" => $crate::__match_err_variant_name!(e),
+                        )
+                        .increment(1);
+                    }
+                }
+                result
+            }
+        }
+    );
+}
@@ -0,0 +1,87 @@
+//! Helpers for a `sqlx::Error` found anywhere in the cause chain, so database failures can be told
+//! apart without matching on messages. Requires the `sqlx` feature
+//!
+//! # Examples
+//! ```
+//! # #[cfg(feature = "sqlx")]
+//! # {
+//!  use match_err::*;
+//!  use match_err::db;
+//!  use anyhow::{anyhow, Context};
+//!
+//!  let res: Result<(), _> = Err(sqlx::Error::RowNotFound);
+//!  let err = res.context("loading user").unwrap_err();
+//!  assert!(db::is_row_not_found(&err));
+//!  assert!(!db::is_unique_violation(&err));
+//!
+//!  let err = anyhow!(sqlx::Error::PoolTimedOut);
+//!  assert!(db::is_pool_timeout(&err));
+//!
+//!  let err = anyhow!(sqlx::Error::Database(Box::new(PgError { code: "23505" })));
+//!  assert!(db::is_unique_violation(&err));
+//!  assert_eq!(db::database_code(&err).as_deref(), Some("23505"));
+//!
+//!  let status = match_err_chain!(err, sqlx::Error, {
+//!     Database(e) if e.is_unique_violation() => 409,
+//!     RowNotFound => 404,
+//!     _ => 500
+//!  });
+//!  assert_eq!(status, 409);
+//!
+//! # #[derive(Debug, thiserror::Error)]
+//! # #[error("database error {code}")]
+//! # struct PgError { code: &'static str }
+//! # impl sqlx::error::DatabaseError for PgError {
+//! #     fn message(&self) -> &str { "duplicate key" }
+//! #     fn code(&self) -> Option<std::borrow::Cow<'_, str>> { Some(self.code.into()) }
+//! #     fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) { self }
+//! #     fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) { self }
+//! #     fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> { self }
+//! #     fn kind(&self) -> sqlx::error::ErrorKind {
+//! #         match self.code { "23505" => sqlx::error::ErrorKind::UniqueViolation, _ => sqlx::error::ErrorKind::Other }
+//! #     }
+//! # }
+//! # }
+//! ```
+
+use crate::Downcastable;
+use sqlx::error::{DatabaseError, ErrorKind};
+use std::borrow::Cow;
+
+/// Returns the first `sqlx::Error` in the cause chain
+pub fn find_sqlx_error<D: Downcastable + ?Sized>(err: &D) -> Option<&sqlx::Error> {
+    err.find_in_chain::<sqlx::Error>()
+}
+
+/// Returns the error returned by the database, if any
+pub fn database_error<D: Downcastable + ?Sized>(err: &D) -> Option<&dyn DatabaseError> {
+    match find_sqlx_error(err)? {
+        sqlx::Error::Database(e) => Some(&**e),
+        _ => None,
+    }
+}
+
+/// Returns the SQLSTATE code of the error returned by the database
+pub fn database_code<D: Downcastable + ?Sized>(err: &D) -> Option<Cow<'_, str>> {
+    database_error(err)?.code()
+}
+
+/// Returns `true` if a unique or primary key constraint was violated
+pub fn is_unique_violation<D: Downcastable + ?Sized>(err: &D) -> bool {
+    database_error(err).is_some_and(|e| matches!(e.kind(), ErrorKind::UniqueViolation))
+}
+
+/// Returns `true` if a foreign key constraint was violated
+pub fn is_foreign_key_violation<D: Downcastable + ?Sized>(err: &D) -> bool {
+    database_error(err).is_some_and(|e| matches!(e.kind(), ErrorKind::ForeignKeyViolation))
+}
+
+/// Returns `true` if a query expected a row but returned none
+pub fn is_row_not_found<D: Downcastable + ?Sized>(err: &D) -> bool {
+    matches!(find_sqlx_error(err), Some(sqlx::Error::RowNotFound))
+}
+
+/// Returns `true` if no connection became available in the pool in time
+pub fn is_pool_timeout<D: Downcastable + ?Sized>(err: &D) -> bool {
+    matches!(find_sqlx_error(err), Some(sqlx::Error::PoolTimedOut))
+}
@@ -0,0 +1,57 @@
+use core::fmt::{self, Display};
+
+/// A line diff of two renderings, with removed lines prefixed by `-` and added ones by `+`.
+/// Colored unless the `NO_COLOR` environment variable is set
+pub(crate) struct Diff<'a> {
+    pub expected: &'a str,
+    pub actual: &'a str,
+}
+
+impl Display for Diff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected: Vec<&str> = self.expected.lines().collect();
+        let actual: Vec<&str> = self.actual.lines().collect();
+        let color = std::env::var_os("NO_COLOR").is_none();
+
+        // lengths of the longest common subsequences of the remaining lines
+        let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+        for i in (0..expected.len()).rev() {
+            for j in (0..actual.len()).rev() {
+                lcs[i][j] = if expected[i] == actual[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        let mut first = true;
+        while i < expected.len() || j < actual.len() {
+            if !first {
+                f.write_str("\n")?;
+            }
+            first = false;
+            if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+                write!(f, "  {}", expected[i])?;
+                i += 1;
+                j += 1;
+            } else if j == actual.len() || (i < expected.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+                line(f, color, "31", '-', expected[i])?;
+                i += 1;
+            } else {
+                line(f, color, "32", '+', actual[j])?;
+                j += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn line(f: &mut fmt::Formatter<'_>, color: bool, code: &str, sign: char, line: &str) -> fmt::Result {
+    if color {
+        write!(f, "\x1b[{code}m{sign} {line}\x1b[0m")
+    } else {
+        write!(f, "{sign} {line}")
+    }
+}
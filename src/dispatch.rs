@@ -0,0 +1,142 @@
+use core::any::TypeId;
+use core::error::Error;
+
+use crate::{Downcastable, ErrorChainIter};
+
+type Handle<R> = Box<dyn Fn(&(dyn Error + 'static)) -> Option<R> + Send + Sync>;
+type Fallback<R> = Box<dyn Fn(&(dyn Error + 'static)) -> R + Send + Sync>;
+
+/// The handlers registered for one error type. The layers of the chain are trait objects whose
+/// `TypeId` can't be read, so they're checked with `is`
+struct Handlers<R> {
+    id: TypeId,
+    is: fn(&(dyn Error + 'static)) -> bool,
+    handlers: Vec<Handler<R>>,
+}
+
+struct Handler<R> {
+    priority: i32,
+    handle: Handle<R>,
+}
+
+/// A registry of error handlers per concrete error type, for application-wide error
+/// reporting and handling that is assembled at runtime instead of written as one big match.
+/// Handlers can have a predicate on the error, like a check of the variant, and a priority. Requires
+/// the `std` feature
+///
+/// Dispatching walks the cause chain from the outermost error and runs a handler for the first
+/// layer that has one whose predicate passes, picking the highest priority and then the earliest
+/// registered. The types are checked in the order they were first registered, so dispatching is
+/// a scan over them rather than a lookup. The default handler runs when none does
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::{anyhow, Context};
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("timeout after {0}s")]
+///     Timeout(u64),
+///  }
+///
+///  let dispatcher = ErrorDispatcher::new()
+///     .on(|e: &Error| format!("app error: {e}"))
+///     .on_if(|e: &Error| matches!(e, Error::Timeout(secs) if *secs > 30), |_| String::from("page"))
+///     .on_with_priority(10, |e: &Error| matches!(e, Error::NotFound), |_| String::from("ignore"))
+///     .on(|e: &std::io::Error| format!("io error: {e}"))
+///     .otherwise(|e| format!("unexpected: {e}"));
+///
+///  assert_eq!(dispatcher.dispatch(&anyhow!(Error::NotFound)).unwrap(), "ignore");
+///  assert_eq!(dispatcher.dispatch(&anyhow!(Error::Timeout(5))).unwrap(), "app error: timeout after 5s");
+///
+///  let res: Result<(), _> = Err(std::io::Error::other("reset"));
+///  let err = res.context("loading user").unwrap_err();
+///  assert_eq!(dispatcher.dispatch(&err).unwrap(), "io error: reset");
+///  assert_eq!(dispatcher.dispatch(&anyhow!("disk full")).unwrap(), "unexpected: disk full");
+///  assert!(dispatcher.handles::<Error>());
+/// ```
+pub struct ErrorDispatcher<R> {
+    types: Vec<Handlers<R>>,
+    default: Option<Fallback<R>>,
+}
+
+impl<R> ErrorDispatcher<R> {
+    /// Creates a dispatcher without handlers
+    pub fn new() -> Self {
+        ErrorDispatcher { types: Vec::new(), default: None }
+    }
+
+    /// Registers a handler for the error type with the priority 0
+    pub fn on<T: Error + 'static>(self, f: impl Fn(&T) -> R + Send + Sync + 'static) -> Self {
+        self.on_with_priority(0, |_: &T| true, f)
+    }
+
+    /// Registers a handler for the errors of the type the predicate returns `true` for, with the
+    /// priority 0
+    pub fn on_if<T: Error + 'static>(
+        self,
+        pred: impl Fn(&T) -> bool + Send + Sync + 'static,
+        f: impl Fn(&T) -> R + Send + Sync + 'static,
+    ) -> Self {
+        self.on_with_priority(0, pred, f)
+    }
+
+    /// Registers a handler for the errors of the type the predicate returns `true` for. Handlers
+    /// with a higher priority are tried first
+    pub fn on_with_priority<T: Error + 'static>(
+        mut self,
+        priority: i32,
+        pred: impl Fn(&T) -> bool + Send + Sync + 'static,
+        f: impl Fn(&T) -> R + Send + Sync + 'static,
+    ) -> Self {
+        let id = TypeId::of::<T>();
+        let index = match self.types.iter().position(|handlers| handlers.id == id) {
+            Some(index) => index,
+            None => {
+                self.types.push(Handlers { id, is: |e| e.is::<T>(), handlers: Vec::new() });
+                self.types.len() - 1
+            }
+        };
+        let entry = &mut self.types[index];
+        let handle: Handle<R> = Box::new(move |e| e.downcast_ref::<T>().filter(|e| pred(e)).map(&f));
+        // after the handlers of the same priority, so they keep the order they were registered in
+        let at = entry.handlers.partition_point(|handler| handler.priority >= priority);
+        entry.handlers.insert(at, Handler { priority, handle });
+        self
+    }
+
+    /// Sets the handler for the errors no other handler is registered for
+    pub fn otherwise(mut self, f: impl Fn(&(dyn Error + 'static)) -> R + Send + Sync + 'static) -> Self {
+        self.default = Some(Box::new(f));
+        self
+    }
+
+    /// Returns `true` if a handler is registered for the error type
+    pub fn handles<T: Error + 'static>(&self) -> bool {
+        self.types.iter().any(|handlers| handlers.id == TypeId::of::<T>())
+    }
+
+    /// Runs the handler for the error, or the default one. Returns `None` if neither applies, or
+    /// the type doesn't expose its error as a trait object
+    pub fn dispatch<D: Downcastable + ?Sized>(&self, err: &D) -> Option<R> {
+        let err = err.to_dyn_error()?;
+        for layer in ErrorChainIter::new(err) {
+            let Some(handlers) = self.types.iter().find(|handlers| (handlers.is)(layer)) else {
+                continue;
+            };
+            if let Some(result) = handlers.handlers.iter().find_map(|handler| (handler.handle)(layer)) {
+                return Some(result);
+            }
+        }
+        self.default.as_ref().map(|f| f(err))
+    }
+}
+
+impl<R> Default for ErrorDispatcher<R> {
+    fn default() -> Self {
+        ErrorDispatcher::new()
+    }
+}
@@ -0,0 +1,519 @@
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::rc::Rc;
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+use core::error::Error;
+
+/// Error values that can be downcast to a concrete error type.
+/// The macros expand against this trait instead of relying on an inherent `downcast_ref` method.
+/// References, `Box`, `Arc` and `Rc` of downcastable errors are downcastable as well, so errors
+/// shared through an `Arc<anyhow::Error>` or an `Arc<dyn Error + Send + Sync>` are matched directly
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use std::error::Error as StdError;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  #[error("upstream failed")]
+///  struct Upstream(#[source] Error);
+///
+///  let err: Box<dyn StdError + Send + Sync> = Box::new(Upstream(Error::NotFound));
+///
+///  assert_eq!(Downcastable::downcast_ref::<Error>(&err), None);
+///  assert_eq!(Downcastable::find_in_chain::<Error>(&err), Some(&Error::NotFound));
+///
+///  let shared = std::sync::Arc::new(anyhow::anyhow!(Error::NotFound));
+///  assert!(match_err!(shared, Error, { NotFound => true, _ => false }));
+///
+///  let shared: std::sync::Arc<dyn StdError + Send + Sync> = std::sync::Arc::new(Error::NotFound);
+///  assert!(matches_err!(shared, Error, NotFound));
+///
+///  let local = std::rc::Rc::new(anyhow::anyhow!(Error::NotFound));
+///  assert!(matches_err!(local, Error, NotFound));
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be downcast to an error type",
+    note = "enable the feature of the crate `{Self}` comes from, or implement `Downcastable` for it"
+)]
+pub trait Downcastable {
+    /// Returns a reference to the error if it's of type `T`
+    fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: Error + Send + Sync + 'static;
+
+    /// Returns a reference to the first error of type `T` in the cause chain.
+    /// Only the error itself is checked unless the type knows how to walk its chain
+    fn find_in_chain<T>(&self) -> Option<&T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        self.downcast_ref::<T>()
+    }
+
+    /// Returns the error as a trait object, which gives access to its `source()` chain.
+    /// `None` for types that don't expose one
+    fn to_dyn_error(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl<D: Downcastable + ?Sized> Downcastable for &D {
+    fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        (**self).downcast_ref::<T>()
+    }
+
+    fn find_in_chain<T>(&self) -> Option<&T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        (**self).find_in_chain::<T>()
+    }
+
+    fn to_dyn_error(&self) -> Option<&(dyn Error + 'static)> {
+        (**self).to_dyn_error()
+    }
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_pointer {
+    ($($ptr:ident),+) => {
+        $(
+            impl<D: Downcastable + ?Sized> Downcastable for $ptr<D> {
+                fn downcast_ref<T>(&self) -> Option<&T>
+                where
+                    T: Error + Send + Sync + 'static,
+                {
+                    (**self).downcast_ref::<T>()
+                }
+
+                fn find_in_chain<T>(&self) -> Option<&T>
+                where
+                    T: Error + Send + Sync + 'static,
+                {
+                    (**self).find_in_chain::<T>()
+                }
+
+                fn to_dyn_error(&self) -> Option<&(dyn Error + 'static)> {
+                    (**self).to_dyn_error()
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "alloc")]
+impl_pointer!(Box, Arc, Rc);
+
+macro_rules! impl_dyn_error {
+    ($($ty:ty),+) => {
+        $(
+            impl Downcastable for $ty {
+                fn downcast_ref<T>(&self) -> Option<&T>
+                where
+                    T: Error + Send + Sync + 'static,
+                {
+                    <$ty>::downcast_ref::<T>(self)
+                }
+
+                fn find_in_chain<T>(&self) -> Option<&T>
+                where
+                    T: Error + Send + Sync + 'static,
+                {
+                    let mut source: Option<&(dyn Error + 'static)> = Some(self);
+                    while let Some(err) = source {
+                        if let Some(e) = downcast_layer::<T>(err) {
+                            return Some(e);
+                        }
+                        source = err.source();
+                    }
+                    None
+                }
+
+                fn to_dyn_error(&self) -> Option<&(dyn Error + 'static)> {
+                    Some(self)
+                }
+            }
+        )+
+    };
+}
+
+impl_dyn_error!(dyn Error + 'static, dyn Error + Send + 'static, dyn Error + Send + Sync + 'static);
+
+#[cfg(feature = "anyhow")]
+impl Downcastable for anyhow::Error {
+    fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        anyhow::Error::downcast_ref::<T>(self)
+    }
+
+    fn find_in_chain<T>(&self) -> Option<&T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        // the inherent downcast also sees through context layers, which the chain doesn't, and
+        // finds a report or a box the error was created from with `Error::msg` or `anyhow!`
+        anyhow::Error::downcast_ref::<T>(self)
+            .or_else(|| (**self).find_in_chain::<T>())
+            .or_else(|| anyhow::Error::downcast_ref::<anyhow::Error>(self)?.find_in_chain::<T>())
+            .or_else(|| anyhow::Error::downcast_ref::<Box<dyn Error + Send + Sync>>(self)?.find_in_chain::<T>())
+    }
+
+    fn to_dyn_error(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.as_ref())
+    }
+}
+
+#[cfg(feature = "eyre")]
+impl Downcastable for eyre::Report {
+    fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        eyre::Report::downcast_ref::<T>(self)
+    }
+
+    fn find_in_chain<T>(&self) -> Option<&T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        eyre::Report::downcast_ref::<T>(self).or_else(|| (**self).find_in_chain::<T>())
+    }
+
+    fn to_dyn_error(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.as_ref())
+    }
+}
+
+#[cfg(feature = "error-stack")]
+impl<C: ?Sized> Downcastable for error_stack::Report<C> {
+    fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        // frames are searched from the most recent one, which covers the whole report
+        error_stack::Report::downcast_ref::<T>(self)
+    }
+}
+
+/// Wraps the error the macros match on, so they can fall back from [`Downcastable`] to plain
+/// errors through autoref: [`ViaDowncastable`] is implemented for `Probe` and wins method resolution,
+/// [`ViaError`] is implemented for `&Probe` and is only picked for errors that aren't `Downcastable`
+#[doc(hidden)]
+pub struct Probe<'a, D: ?Sized>(pub &'a D);
+
+#[doc(hidden)]
+pub trait ViaDowncastable<'a> {
+    fn match_err_downcast<T>(&self) -> Option<&'a T>
+    where
+        T: Error + Send + Sync + 'static;
+
+    fn match_err_find<T>(&self) -> Option<&'a T>
+    where
+        T: Error + Send + Sync + 'static;
+
+    fn match_err_dyn(&self) -> Option<&'a (dyn Error + 'static)>;
+}
+
+impl<'a, D: Downcastable + ?Sized> ViaDowncastable<'a> for Probe<'a, D> {
+    fn match_err_downcast<T>(&self) -> Option<&'a T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        self.0.downcast_ref::<T>()
+    }
+
+    fn match_err_find<T>(&self) -> Option<&'a T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        self.0.find_in_chain::<T>()
+    }
+
+    fn match_err_dyn(&self) -> Option<&'a (dyn Error + 'static)> {
+        self.0.to_dyn_error()
+    }
+}
+
+#[doc(hidden)]
+pub trait ViaError<'a> {
+    fn match_err_downcast<T>(&self) -> Option<&'a T>
+    where
+        T: Error + Send + Sync + 'static;
+
+    fn match_err_find<T>(&self) -> Option<&'a T>
+    where
+        T: Error + Send + Sync + 'static;
+
+    fn match_err_dyn(&self) -> Option<&'a (dyn Error + 'static)>;
+}
+
+impl<'a, E: Error + 'static> ViaError<'a> for &Probe<'a, E> {
+    fn match_err_downcast<T>(&self) -> Option<&'a T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        downcast_layer::<T>(self.0)
+    }
+
+    fn match_err_find<T>(&self) -> Option<&'a T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        let mut source = Some(self.0 as &(dyn Error + 'static));
+        while let Some(e) = source {
+            if let Some(e) = downcast_layer::<T>(e) {
+                return Some(e);
+            }
+            source = e.source();
+        }
+        None
+    }
+
+    fn match_err_dyn(&self) -> Option<&'a (dyn Error + 'static)> {
+        Some(self.0)
+    }
+}
+
+/// Downcasts a layer of the cause chain, seeing through a `Box` or an `Arc` around the error,
+/// whose `source()` skips it
+pub(crate) fn downcast_layer<'a, T>(layer: &'a (dyn Error + 'static)) -> Option<&'a T>
+where
+    T: Error + Send + Sync + 'static,
+{
+    if let Some(e) = layer.downcast_ref::<T>() {
+        return Some(e);
+    }
+    #[cfg(feature = "alloc")]
+    {
+        if let Some(e) = layer.downcast_ref::<Box<T>>() {
+            return Some(e);
+        }
+        if let Some(e) = layer.downcast_ref::<Arc<T>>() {
+            return Some(e);
+        }
+        if let Some(e) = layer.downcast_ref::<Arc<dyn Error + Send + Sync>>() {
+            return downcast_layer::<T>(&**e);
+        }
+    }
+    None
+}
+
+/// Gives access to the error behind the `#[error(transparent)]` variants of an error type, whose
+/// `source()` skips it. Implemented by the `MatchErr` derive for types with such variants
+#[doc(hidden)]
+pub trait Transparent {
+    fn transparent(&self) -> Option<&(dyn Error + 'static)>;
+}
+
+/// Returns the last error of the `source()` chain from `root`
+#[doc(hidden)]
+pub fn root_cause<'a>(root: Option<&'a (dyn Error + 'static)>) -> Option<&'a (dyn Error + 'static)> {
+    let mut layer = root?;
+    while let Some(source) = layer.source() {
+        layer = source;
+    }
+    Some(layer)
+}
+
+/// Returns the error `depth` levels down the `source()` chain from `root`
+#[doc(hidden)]
+pub fn source_at<'a>(root: Option<&'a (dyn Error + 'static)>, depth: usize) -> Option<&'a (dyn Error + 'static)> {
+    let mut layer = root;
+    for _ in 0..depth {
+        layer = layer?.source();
+    }
+    layer
+}
+
+/// Walks the cause chain from `root` looking for an error of type `T`, stepping into the error
+/// behind a transparent wrapper with `unwrap` and to the `source()` otherwise
+#[doc(hidden)]
+pub fn find_through<'a, T>(
+    root: Option<&'a (dyn Error + 'static)>,
+    unwrap: for<'e> fn(&'e (dyn Error + 'static)) -> Option<&'e (dyn Error + 'static)>,
+) -> Option<&'a T>
+where
+    T: Error + Send + Sync + 'static,
+{
+    let mut layer = root;
+    while let Some(e) = layer {
+        if let Some(e) = downcast_layer::<T>(e) {
+            return Some(e);
+        }
+        layer = unwrap(e).or_else(|| e.source());
+    }
+    None
+}
+
+/// Error values that can be consumed into a concrete error type, giving back the original
+/// value when it's of another type. Used by [`match_err_owned!`](crate::match_err_owned)
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let err = anyhow!(Error::Custom("boom".to_string()));
+///  assert_eq!(DowncastOwned::downcast::<Error>(err).ok(), Some(Error::Custom("boom".to_string())));
+///
+///  let err = anyhow!("unknown");
+///  assert_eq!(DowncastOwned::downcast::<Error>(err).unwrap_err().to_string(), "unknown");
+/// ```
+pub trait DowncastOwned: Sized {
+    /// Returns the error if it's of type `T`, the original value otherwise
+    fn downcast<T>(self) -> Result<T, Self>
+    where
+        T: Error + Send + Sync + 'static;
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_dyn_error_owned {
+    ($($ty:ty),+) => {
+        $(
+            impl DowncastOwned for Box<$ty> {
+                fn downcast<T>(self) -> Result<T, Self>
+                where
+                    T: Error + Send + Sync + 'static,
+                {
+                    <$ty>::downcast::<T>(self).map(|e| *e)
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "alloc")]
+impl_dyn_error_owned!(dyn Error + 'static, dyn Error + Send + 'static, dyn Error + Send + Sync + 'static);
+
+#[cfg(feature = "anyhow")]
+impl DowncastOwned for anyhow::Error {
+    fn downcast<T>(self) -> Result<T, Self>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        anyhow::Error::downcast::<T>(self)
+    }
+}
+
+#[cfg(feature = "eyre")]
+impl DowncastOwned for eyre::Report {
+    fn downcast<T>(self) -> Result<T, Self>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        eyre::Report::downcast::<T>(self)
+    }
+}
+
+/// Error values that can be downcast to a mutable reference of a concrete error type.
+/// Used by [`match_err_mut!`](crate::match_err_mut)
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let mut err = anyhow!(Error::Custom("secret".to_string()));
+///  if let Some(Error::Custom(msg)) = DowncastMut::downcast_mut::<Error>(&mut err) {
+///     msg.clear();
+///  }
+///  assert_eq!(err.to_string(), "custom: ");
+/// ```
+pub trait DowncastMut {
+    /// Returns a mutable reference to the error if it's of type `T`
+    fn downcast_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Error + Send + Sync + 'static;
+}
+
+impl<D: DowncastMut + ?Sized> DowncastMut for &mut D {
+    fn downcast_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        (**self).downcast_mut::<T>()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D: DowncastMut + ?Sized> DowncastMut for Box<D> {
+    fn downcast_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        (**self).downcast_mut::<T>()
+    }
+}
+
+macro_rules! impl_dyn_error_mut {
+    ($($ty:ty),+) => {
+        $(
+            impl DowncastMut for $ty {
+                fn downcast_mut<T>(&mut self) -> Option<&mut T>
+                where
+                    T: Error + Send + Sync + 'static,
+                {
+                    <$ty>::downcast_mut::<T>(self)
+                }
+            }
+        )+
+    };
+}
+
+impl_dyn_error_mut!(dyn Error + 'static, dyn Error + Send + 'static, dyn Error + Send + Sync + 'static);
+
+#[cfg(feature = "anyhow")]
+impl DowncastMut for anyhow::Error {
+    fn downcast_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        anyhow::Error::downcast_mut::<T>(self)
+    }
+}
+
+#[cfg(feature = "eyre")]
+impl DowncastMut for eyre::Report {
+    fn downcast_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        eyre::Report::downcast_mut::<T>(self)
+    }
+}
+
+#[cfg(feature = "error-stack")]
+impl<C: ?Sized> DowncastMut for error_stack::Report<C> {
+    fn downcast_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        error_stack::Report::downcast_mut::<T>(self)
+    }
+}
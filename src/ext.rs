@@ -0,0 +1,164 @@
+use core::error::Error;
+
+use crate::Downcastable;
+
+/// Matching on the error of a `Result` with closures, for fluent chains where the macros are awkward
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let results: Vec<Result<u32, anyhow::Error>> = vec![
+///     Ok(1),
+///     Err(anyhow!(Error::NotFound)),
+///     Err(anyhow!(Error::Custom(String::from("internal")))),
+///  ];
+///
+///  let codes: Vec<_> = results
+///     .iter()
+///     .filter_map(|res| res.match_err::<Error, _>(|e| match e {
+///         Error::NotFound => 404,
+///         Error::Custom(_) => 500,
+///     }))
+///     .collect();
+///  assert_eq!(codes, vec![404, 500]);
+///
+///  let values: Vec<_> = results
+///     .into_iter()
+///     .map(|res| res.on_err_variant(Error::NotFound, || 0))
+///     .collect();
+///  assert_eq!(values[0].as_ref().unwrap(), &1);
+///  assert_eq!(values[1].as_ref().unwrap(), &0);
+///  assert!(values[2].is_err());
+/// ```
+pub trait ResultExt<T, D> {
+    /// Calls `f` with the error if it's of type `E`, otherwise returns `None`
+    fn match_err<E, R>(&self, f: impl FnOnce(&E) -> R) -> Option<R>
+    where
+        E: Error + Send + Sync + 'static;
+
+    /// Replaces the error with the value returned by `f` if it equals `variant`,
+    /// other errors are passed through
+    fn on_err_variant<E>(self, variant: E, f: impl FnOnce() -> T) -> Result<T, D>
+    where
+        E: Error + Send + Sync + PartialEq + 'static;
+}
+
+impl<T, D: Downcastable> ResultExt<T, D> for Result<T, D> {
+    fn match_err<E, R>(&self, f: impl FnOnce(&E) -> R) -> Option<R>
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        self.as_ref().err()?.downcast_ref::<E>().map(f)
+    }
+
+    fn on_err_variant<E>(self, variant: E, f: impl FnOnce() -> T) -> Result<T, D>
+    where
+        E: Error + Send + Sync + PartialEq + 'static,
+    {
+        match self {
+            Err(err) if err.downcast_ref::<E>() == Some(&variant) => Ok(f()),
+            res => res,
+        }
+    }
+}
+
+/// Variant checks on error values that already exist, so they don't need the macro form.
+/// The cause chain can be searched with [`Downcastable::find_in_chain`]
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum ErrorKind {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  #[error("upstream failed")]
+///  struct Upstream(#[source] ErrorKind);
+///
+///  let err = anyhow!(ErrorKind::NotFound);
+///  assert!(err.is_variant(ErrorKind::NotFound));
+///  assert!(!err.is_variant(ErrorKind::Custom(String::from("internal"))));
+///  assert_eq!(err.downcast_variant(ErrorKind::NotFound), Some(&ErrorKind::NotFound));
+///
+///  let err = anyhow!(Upstream(ErrorKind::NotFound));
+///  assert!(!err.is_variant(ErrorKind::NotFound));
+///  assert!(err.is_variant_in_chain(ErrorKind::NotFound));
+///  assert_eq!(err.find_in_chain::<ErrorKind>(), Some(&ErrorKind::NotFound));
+/// ```
+pub trait AnyErrorExt: Downcastable {
+    /// Returns `true` if the error is of type `E` and equals `variant`
+    fn is_variant<E>(&self, variant: E) -> bool
+    where
+        E: Error + Send + Sync + PartialEq + 'static,
+    {
+        self.downcast_variant(variant).is_some()
+    }
+
+    /// Returns `true` if an error in the cause chain is of type `E` and equals `variant`
+    fn is_variant_in_chain<E>(&self, variant: E) -> bool
+    where
+        E: Error + Send + Sync + PartialEq + 'static,
+    {
+        self.find_in_chain::<E>() == Some(&variant)
+    }
+
+    /// Returns a reference to the error if it's of type `E` and equals `variant`
+    fn downcast_variant<E>(&self, variant: E) -> Option<&E>
+    where
+        E: Error + Send + Sync + PartialEq + 'static,
+    {
+        self.downcast_ref::<E>().filter(|e| **e == variant)
+    }
+}
+
+impl<D: Downcastable + ?Sized> AnyErrorExt for D {}
+
+/// Values that may hold an error, so [`match_if_err!`](crate::match_if_err) takes an `Option` of an
+/// error as well as a `Result`
+#[doc(hidden)]
+pub trait MaybeErr {
+    type Err: ?Sized;
+
+    fn maybe_err(&self) -> Option<&Self::Err>;
+}
+
+impl<T, E> MaybeErr for Result<T, E> {
+    type Err = E;
+
+    fn maybe_err(&self) -> Option<&E> {
+        self.as_ref().err()
+    }
+}
+
+impl<E> MaybeErr for Option<E> {
+    type Err = E;
+
+    fn maybe_err(&self) -> Option<&E> {
+        self.as_ref()
+    }
+}
+
+impl<M: MaybeErr + ?Sized> MaybeErr for &M {
+    type Err = M::Err;
+
+    fn maybe_err(&self) -> Option<&M::Err> {
+        (**self).maybe_err()
+    }
+}
@@ -0,0 +1,36 @@
+/// Builds an `anyhow::Error` with the given cause chain, for feeding realistic wrapped errors into
+/// code under test. The first item is the root cause, a string literal or an error value, and
+/// every following one wraps the chain so far as `anyhow` context, so the last one is the error
+/// itself. Requires the `anyhow` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use std::io::{self, ErrorKind};
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum StoreError {
+///     #[error("record not found")]
+///     NotFound,
+///  }
+///
+///  let err = chain_err![io::Error::from(ErrorKind::NotFound), StoreError::NotFound, "while loading user"];
+///
+///  let chain: Vec<_> = err.chain().map(|e| e.to_string()).collect();
+///  assert_eq!(chain, ["while loading user", "record not found", "entity not found"]);
+///  assert!(matches_err!(err, StoreError, NotFound));
+///  assert_eq!(err.find_in_chain::<io::Error>().map(io::Error::kind), Some(ErrorKind::NotFound));
+///
+///  let err = chain_err!["connection reset", "while syncing"];
+///  assert_eq!(format!("{err:#}"), "while syncing: connection reset");
+/// ```
+#[macro_export]
+macro_rules! chain_err {
+    ( $root:literal $(, $layer:expr)* $(,)? ) => (
+        $crate::__private::anyhow::Error::msg($root) $( .context($layer) )*
+    );
+
+    ( $root:expr $(, $layer:expr)* $(,)? ) => (
+        $crate::__private::anyhow::Error::new($root) $( .context($layer) )*
+    );
+}
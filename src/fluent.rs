@@ -0,0 +1,97 @@
+use core::error::Error;
+use core::mem;
+
+use crate::Downcastable;
+
+/// Matches an error by chaining handlers at runtime instead of writing the arms of
+/// [`match_err!`](crate::match_err), for dispatch tables that are only known at runtime. The first
+/// handler whose check passes runs, and the later ones are skipped
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("timeout after {0}s")]
+///     Timeout(u64),
+///  }
+///
+///  fn status(err: &anyhow::Error, retry_after: u64) -> u16 {
+///     ErrMatch::new(err)
+///         .on_same_variant(Error::NotFound, || 404)
+///         .on_if(|e: &Error| matches!(e, Error::Timeout(secs) if *secs > retry_after), |_| 504)
+///         .on::<std::io::Error>(|_| 503)
+///         .otherwise(|_| 500)
+///  }
+///
+///  assert_eq!(status(&anyhow!(Error::NotFound), 10), 404);
+///  assert_eq!(status(&anyhow!(Error::Timeout(30)), 10), 504);
+///  assert_eq!(status(&anyhow!(Error::Timeout(5)), 10), 500);
+///  assert_eq!(status(&anyhow!(std::io::Error::other("reset")), 10), 503);
+///
+///  let err = anyhow!(Error::Timeout(5));
+///  let mut handled = ErrMatch::new(&err);
+///  for variant in [Error::NotFound, Error::Timeout(0)] {
+///     handled = handled.on_same_variant(variant, || "handled");
+///  }
+///  assert_eq!(handled.matched(), Some("handled"));
+///
+///  let err = anyhow!(Error::Timeout(5));
+///  assert_eq!(ErrMatch::new(&err).on_same_variant(Error::Timeout(30), || "timeout").matched(), Some("timeout"));
+/// ```
+pub struct ErrMatch<'a, D: ?Sized, R> {
+    err: &'a D,
+    result: Option<R>,
+}
+
+impl<'a, D: Downcastable + ?Sized, R> ErrMatch<'a, D, R> {
+    /// Starts matching the error
+    pub fn new(err: &'a D) -> Self {
+        ErrMatch { err, result: None }
+    }
+
+    /// Runs the handler if the error is of the type
+    pub fn on<T: Error + Send + Sync + 'static>(self, f: impl FnOnce(&'a T) -> R) -> Self {
+        self.on_if(|_: &T| true, f)
+    }
+
+    /// Runs the handler if the error is of the type and the predicate returns `true`
+    pub fn on_if<T: Error + Send + Sync + 'static>(
+        mut self,
+        pred: impl FnOnce(&'a T) -> bool,
+        f: impl FnOnce(&'a T) -> R,
+    ) -> Self {
+        if self.result.is_none() {
+            if let Some(e) = self.err.downcast_ref::<T>() {
+                if pred(e) {
+                    self.result = Some(f(e));
+                }
+            }
+        }
+        self
+    }
+
+    /// Runs the handler if the error is the same variant of an enum-like error type as the given
+    /// value. Unlike [`AnyErrorExt::is_variant`](crate::AnyErrorExt::is_variant) only the variants
+    /// are compared, the payloads are ignored, so `Code(404)` is the same variant as `Code(500)`
+    pub fn on_same_variant<T: Error + Send + Sync + 'static>(self, variant: T, f: impl FnOnce() -> R) -> Self {
+        self.on_if(|e: &T| mem::discriminant(e) == mem::discriminant(&variant), |_| f())
+    }
+
+    /// Returns the result of the handler that ran, or runs the fallback with the error
+    pub fn otherwise(self, f: impl FnOnce(&'a D) -> R) -> R {
+        match self.result {
+            Some(result) => result,
+            None => f(self.err),
+        }
+    }
+
+    /// Returns the result of the handler that ran, if any
+    pub fn matched(self) -> Option<R> {
+        self.result
+    }
+}
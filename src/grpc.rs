@@ -0,0 +1,93 @@
+/// Finds a `tonic::Status` anywhere in the cause chain and matches on its `tonic::Code`, like
+/// [`match_io_err!`] does for io errors. Requires the `tonic` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "tonic")]
+/// # {
+///  use match_err::*;
+///  use anyhow::{anyhow, Context};
+///  use tonic::Status;
+///
+///  let retry = |err: anyhow::Error| match_status!(err, {
+///     Unavailable | DeadlineExceeded => true,
+///     Aborted if err.to_string().contains("conflict") => true,
+///     _ => false
+///  });
+///
+///  let res: Result<(), _> = Err(Status::unavailable("overloaded"));
+///  assert!(retry(res.context("calling users").unwrap_err()));
+///  assert!(!retry(anyhow!(Status::not_found("user 42"))));
+///  assert!(!retry(anyhow!("unknown")));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! match_status {
+    ( $any:expr, { $($arms:tt)* } ) => (
+        $crate::__match_err_keyed!(any => $crate::__match_err_probe!(match_err_find any, $crate::__private::tonic::Status).map(|status| status.code()), [$crate::__private::tonic::Code], $any, { $($arms)* })
+    );
+}
+
+/// Converts the error into a `tonic::Status` with the code of the matching arm and the error
+/// rendered with `Display` as the message. Without a `_` arm other errors get `Code::Internal`.
+/// Requires the `tonic` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "tonic")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use tonic::Code;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("user {0} not found")]
+///     NotFound(u64),
+///     #[error("invalid {0}")]
+///     Invalid(String),
+///  }
+///
+///  let status = err_to_grpc_status!(anyhow!(Error::NotFound(42)), Error, {
+///     NotFound(_) => Code::NotFound,
+///     Invalid(_) => Code::InvalidArgument
+///  });
+///  assert_eq!(status.code(), Code::NotFound);
+///  assert_eq!(status.message(), "user 42 not found");
+///
+///  let status = err_to_grpc_status!(anyhow!("unknown"), Error, { Invalid(_) => Code::InvalidArgument });
+///  assert_eq!(status.code(), Code::Internal);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! err_to_grpc_status {
+    (@status $any:ident $code:expr) => ({
+        let code: $crate::__private::tonic::Code = $code;
+        $crate::__private::tonic::Status::new(code, ::std::string::ToString::to_string($any))
+    });
+
+    (@arms $any:ident $ty:tt [$($arms:tt)*] _ => $default:expr $(,)?) => (
+        $crate::err_to_grpc_status!(@status $any $crate::match_err!(@arms top $any $ty [] $($arms)* _ => $default))
+    );
+
+    (@arms $any:ident $ty:tt [$($arms:tt)*] $bind:ident @ _ => $default:expr $(,)?) => (
+        $crate::err_to_grpc_status!(@status $any $crate::match_err!(@arms top $any $ty [] $($arms)* $bind @ _ => $default))
+    );
+
+    (@arms $any:ident $ty:tt [$($arms:tt)*] $(,)?) => (
+        $crate::err_to_grpc_status!(@status $any $crate::match_err!(@arms top $any $ty [] $($arms)* _ => $crate::__private::tonic::Code::Internal))
+    );
+
+    (@arms $any:ident $ty:tt [$($arms:tt)*] $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::err_to_grpc_status!(@arms $any $ty [
+            $($arms)*
+            $( #[$attr] )* $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => $arm,
+        ] $($($rest)*)?)
+    );
+
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        match &$any {
+            any => $crate::err_to_grpc_status!(@arms any [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*)
+        }
+    );
+}
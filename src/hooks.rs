@@ -0,0 +1,78 @@
+//! Process-wide hooks called whenever the matching and asserting macros find an error of a type,
+//! so a library can have its reporter called for an `Error::Corrupted` wherever it gets matched.
+//! The hooks run once the error is downcast to the type, before the arms, with any variant, so
+//! they pick the variants they care about themselves. Requires the `hooks` feature
+//!
+//! Hooks run while the registry is locked, so they must not register hooks themselves
+//!
+//! ```
+//! # #[cfg(feature = "hooks")]
+//! # {
+//!  use match_err::*;
+//!  use anyhow::anyhow;
+//!  use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//!  #[derive(thiserror::Error, Debug)]
+//!  enum Error {
+//!     #[error("corrupted block {0}")]
+//!     Corrupted(u64),
+//!     #[error("not found")]
+//!     NotFound,
+//!  }
+//!
+//!  static CORRUPTED: AtomicUsize = AtomicUsize::new(0);
+//!  hooks::register(|e: &Error| {
+//!     if let Error::Corrupted(_) = e {
+//!         CORRUPTED.fetch_add(1, Ordering::Relaxed);
+//!     }
+//!  });
+//!
+//!  let status = match_err!(anyhow!(Error::Corrupted(7)), Error, { NotFound => 404, _ => 500 });
+//!  assert_eq!(status, 500);
+//!  assert_err_matches!(anyhow!(Error::Corrupted(8)), Error::Corrupted(_));
+//!  match_err!(anyhow!(Error::NotFound), Error, { NotFound => {} });
+//!  assert_eq!(CORRUPTED.load(Ordering::Relaxed), 2);
+//! # }
+//! ```
+
+use core::any::TypeId;
+use core::error::Error;
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{PoisonError, RwLock};
+
+type Call = Box<dyn Fn(&(dyn Error + 'static)) + Send + Sync>;
+
+struct Hook {
+    type_id: TypeId,
+    call: Call,
+}
+
+static HOOKS: RwLock<Vec<Hook>> = RwLock::new(Vec::new());
+/// Skips locking the registry until a hook is registered
+static REGISTERED: AtomicBool = AtomicBool::new(false);
+
+/// Registers a hook called with every error of the type found by the macros, in the order the
+/// hooks were registered
+pub fn register<T: Error + 'static>(hook: impl Fn(&T) + Send + Sync + 'static) {
+    let call = Box::new(move |e: &(dyn Error + 'static)| {
+        if let Some(e) = e.downcast_ref::<T>() {
+            hook(e);
+        }
+    });
+    HOOKS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(Hook { type_id: TypeId::of::<T>(), call });
+    REGISTERED.store(true, Ordering::Release);
+}
+
+#[doc(hidden)]
+pub fn run<T: Error + 'static>(err: &T) {
+    if !REGISTERED.load(Ordering::Acquire) {
+        return;
+    }
+    let hooks = HOOKS.read().unwrap_or_else(PoisonError::into_inner);
+    for hook in hooks.iter().filter(|hook| hook.type_id == TypeId::of::<T>()) {
+        (hook.call)(err);
+    }
+}
@@ -0,0 +1,38 @@
+/// Finds an `std::io::Error` anywhere in the cause chain and matches on its `ErrorKind`.
+/// The fallback arm handles other kinds and errors without an io error, like in [`match_err!`].
+/// Requires the `std` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::{anyhow, Context};
+///  use std::io;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum ConfigError {
+///     #[error("can't read config")]
+///     Read(#[source] io::Error),
+///  }
+///
+///  let describe = |err: anyhow::Error| match_io_err!(err, {
+///     NotFound => String::from("missing"),
+///     PermissionDenied | AlreadyExists => String::from("forbidden"),
+///     Interrupted if err.to_string().contains("retry") => String::from("retry"),
+///     e @ _ => format!("other: {e}")
+///  });
+///
+///  let err = anyhow!(ConfigError::Read(io::Error::from(io::ErrorKind::NotFound)));
+///  assert_eq!(describe(err), "missing");
+///
+///  let res: Result<(), _> = Err(io::Error::from(io::ErrorKind::PermissionDenied));
+///  assert_eq!(describe(res.context("opening config").unwrap_err()), "forbidden");
+///
+///  assert_eq!(describe(anyhow!(io::Error::from(io::ErrorKind::TimedOut))), "other: timed out");
+///  assert_eq!(describe(anyhow!("unknown")), "other: unknown");
+/// ```
+#[macro_export]
+macro_rules! match_io_err {
+    ( $any:expr, { $($arms:tt)* } ) => (
+        $crate::__match_err_keyed!(any => $crate::__match_err_probe!(match_err_find any, ::std::io::Error).map(|e| e.kind()), [::std::io::ErrorKind], $any, { $($arms)* })
+    );
+}
@@ -3,7 +3,7 @@
 //! Macro for quick matching and asserting errors against enum-like error types
 //!
 //! Helps to avoid writing long and tedious structures like:
-//! ```rust
+//! ```ignore
 //! if let Err(e) = err {
 //!     if let Some(e) = e.downcast_ref::<Error>() {
 //!         match e {
@@ -35,172 +35,4086 @@
 //!     _ => println!("unknown")
 //! })
 //! ```
+//!
+//! ## Boxed errors
+//!
+//! The macros expand against the [`Downcastable`] trait, so besides `anyhow::Error` they work with
+//! `Box<dyn std::error::Error + Send + Sync>` and results carrying it
+//!
+//! ```rust
+//! use match_err::*;
+//! use std::error::Error as StdError;
+//!
+//! #[derive(thiserror::Error, Debug, PartialEq)]
+//! enum Error {
+//!     #[error("not found")]
+//!     NotFound,
+//!     #[error("custom: {0}")]
+//!     Custom(String),
+//! }
+//!
+//! #[derive(thiserror::Error, Debug)]
+//! #[error("upstream failed")]
+//! struct Upstream(#[source] Error);
+//!
+//! fn find() -> Result<(), Box<dyn StdError + Send + Sync>> {
+//!     Err(Box::new(Error::NotFound))
+//! }
+//!
+//! let res = find();
+//! match_if_err!(res, Error, {
+//!     NotFound => assert!(true),
+//!     _ => assert!(false)
+//! });
+//! assert_if_error!(res, Error, NotFound);
+//! assert_eq!(unwrap_err_as!(res, Error), &Error::NotFound);
+//!
+//! let err: Box<dyn StdError + Send + Sync> = Box::new(Upstream(Error::Custom(String::from("internal"))));
+//! assert!(!matches_err!(err, Error, Custom(_)));
+//! match_err_chain!(err, Error, {
+//!     Custom(msg) => assert_eq!(msg, "internal"),
+//!     _ => assert!(false)
+//! });
+//! assert_err_matches!(Box::<dyn StdError>::from(Error::NotFound), Error::NotFound);
+//! ```
+//!
+//! Plain `&dyn std::error::Error` references, e.g. the ones returned by `source()`, go through
+//! `<dyn Error>::downcast_ref` the same way
+//!
+//! ```rust
+//! use match_err::*;
+//! use std::error::Error as StdError;
+//!
+//! #[derive(thiserror::Error, Debug, PartialEq)]
+//! enum Error {
+//!     #[error("not found")]
+//!     NotFound,
+//! }
+//!
+//! #[derive(thiserror::Error, Debug)]
+//! #[error("upstream failed")]
+//! struct Upstream(#[source] Error);
+//!
+//! let err = Upstream(Error::NotFound);
+//! let source: &dyn StdError = err.source().unwrap();
+//!
+//! match_err!(source, Error, {
+//!     NotFound => assert!(true),
+//!     _ => assert!(false)
+//! });
+//! assert!(matches_err!(err.source().unwrap(), Error, NotFound));
+//! assert_error!(source, Error, NotFound);
+//! assert_err_matches!(source, Error::NotFound);
+//! match_err_chain!(&err as &dyn StdError, Error, {
+//!     NotFound => assert!(true),
+//!     _ => assert!(false)
+//! });
+//! ```
+//!
+//! ## Concrete errors
+//!
+//! Errors that aren't type-erased at all, like the error of a `Result<T, Error>`, are downcast
+//! through the same expansion, so the macros take them as they are
+//!
+//! ```rust
+//! use match_err::*;
+//!
+//! #[derive(thiserror::Error, Debug, PartialEq)]
+//! enum Error {
+//!     #[error("not found")]
+//!     NotFound,
+//!     #[error("custom: {0}")]
+//!     Custom(String),
+//! }
+//!
+//! let res: Result<u32, Error> = Err(Error::NotFound);
+//! let e = Error::NotFound;
+//!
+//! assert_eq!(if_err_is!(e, Error::NotFound, { 404 } else { 500 }), 404);
+//! let_err_as!(Error::NotFound = e else { panic!("not found expected") });
+//! assert_error!(e, Error, NotFound);
+//! assert_if_error!(res, Error, NotFound);
+//! assert_err_matches!(e, Error::NotFound | Error::Custom(_));
+//! assert_eq!(unwrap_err_as!(res, Error), &Error::NotFound);
+//! assert_eq!(check_error!(e, Error, NotFound), Ok(()));
+//! assert_eq!(check_error!(e, Error, Custom(String::from("a"))).unwrap_err().kind(), Mismatch::Variant);
+//! assert!(verify_error!(e, Error, NotFound).is_ok());
+//! assert_err_matches!(res.unwrap_err(), Error::NotFound);
+//!
+//! fn load(res: Result<u32, Error>) -> anyhow::Result<u32> {
+//!     let res = bail_if_err_is!(res, Error::Custom(msg) if msg == "fatal", "fatal error");
+//!     ensure_ok_or_variant!(res, Error::NotFound)?;
+//!     Ok(1)
+//! }
+//!
+//! assert_eq!(load(Err(Error::NotFound)).unwrap(), 1);
+//! assert_eq!(load(Err(Error::Custom(String::from("fatal")))).unwrap_err().to_string(), "fatal error");
+//! assert_eq!(load(Err(Error::Custom(String::from("io")))).unwrap_err().to_string(), "custom: io");
+//! ```
+//!
+//! ## no_std
+//!
+//! With `default-features = false` the crate is `no_std`, and the macros work with
+//! `&dyn core::error::Error`. The `alloc` feature brings back the `Box<dyn Error>` support
+//!
+//! ```rust
+//! use match_err::*;
+//! use core::error::Error as CoreError;
+//!
+//! #[derive(Debug)]
+//! enum Error {
+//!     NotFound,
+//! }
+//!
+//! impl core::fmt::Display for Error {
+//!     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+//!         f.write_str("not found")
+//!     }
+//! }
+//!
+//! impl CoreError for Error {}
+//!
+//! let err: &(dyn CoreError + Send + Sync) = &Error::NotFound;
+//! match_err_chain!(err, Error, {
+//!     NotFound => assert!(true),
+//!     _ => assert!(false)
+//! });
+//! ```
+//!
+//! ## eyre
+//!
+//! With the `eyre` feature [`Downcastable`] is implemented for `eyre::Report`, so the macros work
+//! with it the same way, including matching across the cause chain. The `anyhow` feature is on by
+//! default and can be turned off with `default-features = false`
+//!
+//! ```rust
+//! # #[cfg(feature = "eyre")]
+//! # {
+//! use match_err::*;
+//! use eyre::{eyre, WrapErr};
+//!
+//! #[derive(thiserror::Error, Debug, PartialEq)]
+//! enum Error {
+//!     #[error("not found")]
+//!     NotFound,
+//!     #[error("custom: {0}")]
+//!     Custom(String),
+//! }
+//!
+//! #[derive(thiserror::Error, Debug)]
+//! #[error("upstream failed")]
+//! struct Upstream(#[source] Error);
+//!
+//! let res: eyre::Result<()> = Err(eyre!(Error::NotFound));
+//! match_if_err!(res, Error, {
+//!     NotFound => assert!(true),
+//!     _ => assert!(false)
+//! });
+//! assert_if_error!(res, Error, NotFound);
+//!
+//! let err = Err::<(), _>(Upstream(Error::Custom(String::from("internal"))))
+//!     .wrap_err("handling request")
+//!     .unwrap_err();
+//! match_err_chain!(err, Error, {
+//!     Custom(msg) => assert_eq!(msg, "internal"),
+//!     _ => assert!(false)
+//! });
+//! # }
+//! ```
+//!
+//! ## error-stack
+//!
+//! With the `error-stack` feature [`Downcastable`] is implemented for `error_stack::Report`.
+//! Its `downcast_ref` iterates over all frames of the report, so [`match_err!`] finds a context
+//! that was changed afterwards as well
+//!
+//! ```rust
+//! # #[cfg(feature = "error-stack")]
+//! # {
+//! use match_err::*;
+//! use error_stack::Report;
+//!
+//! #[derive(thiserror::Error, Debug, PartialEq)]
+//! enum Error {
+//!     #[error("not found")]
+//!     NotFound,
+//!     #[error("custom: {0}")]
+//!     Custom(String),
+//! }
+//!
+//! #[derive(thiserror::Error, Debug)]
+//! #[error("request failed")]
+//! struct RequestError;
+//!
+//! let res: Result<(), Report<Error>> = Err(Report::new(Error::NotFound));
+//! match_if_err!(res, Error, {
+//!     NotFound => assert!(true),
+//!     _ => assert!(false)
+//! });
+//! assert_if_error!(res, Error, NotFound);
+//!
+//! let report = Report::new(Error::Custom(String::from("internal")))
+//!     .attach("while loading user")
+//!     .change_context(RequestError);
+//! match_err!(report, Error, {
+//!     Custom(msg) => assert_eq!(msg, "internal"),
+//!     _ => assert!(false)
+//! });
+//! assert_error!(report, Error, Custom(String::from("internal")));
+//! assert!(matches_err!(report, Error, Custom(_)));
+//! match_err_chain!(report, Error, {
+//!     Custom(_) => assert!(true),
+//!     _ => assert!(false)
+//! });
+//! # }
+//! ```
 
 
-/// Matches the error against an enum-like error type by hiding the usage of downcast_ref method
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod assert;
+mod chain;
+mod class;
+mod code;
+#[cfg(feature = "pretty")]
+mod diff;
+#[cfg(feature = "metrics")]
+mod counter;
+#[cfg(feature = "sqlx")]
+pub mod db;
+#[cfg(feature = "std")]
+mod dispatch;
+mod downcast;
+mod ext;
+#[cfg(feature = "anyhow")]
+mod fixture;
+mod fluent;
+#[cfg(feature = "tonic")]
+mod grpc;
+#[cfg(feature = "hooks")]
+pub mod hooks;
+#[cfg(feature = "std")]
+mod io;
+mod map;
+#[cfg(feature = "alloc")]
+mod multi;
+#[cfg(feature = "googletest")]
+pub mod matchers;
+mod name;
+#[cfg(feature = "reqwest")]
+pub mod net;
+#[cfg(feature = "std")]
+mod panic;
+#[cfg(feature = "std")]
+mod partition;
+#[cfg(feature = "std")]
+mod report;
+mod retry;
+#[cfg(feature = "insta")]
+pub mod snapshot;
+#[cfg(feature = "http")]
+mod status;
+#[cfg(feature = "alloc")]
+mod stats;
+#[cfg(feature = "macros")]
+mod strict;
+#[cfg(feature = "tokio")]
+pub mod task;
+#[cfg(feature = "tracing")]
+mod trace;
+mod variant;
+
+#[cfg(feature = "alloc")]
+pub use assert::{ErrorAssertions, ErrorMismatch};
+pub use assert::Mismatch;
+#[cfg(feature = "alloc")]
+pub use chain::collect_in_chain;
+pub use chain::{caused_by_variant, find_in_chain, is_caused_by, ErrorChainIter};
+pub use class::{Classify, ErrorClass};
+pub use code::{Code, ErrorCode};
+#[cfg(feature = "std")]
+pub use dispatch::ErrorDispatcher;
+pub use downcast::{DowncastMut, DowncastOwned, Downcastable};
+pub use ext::{AnyErrorExt, ResultExt};
+pub use fluent::ErrMatch;
+#[cfg(feature = "alloc")]
+pub use multi::MultiError;
+pub use name::VariantName;
+#[cfg(feature = "std")]
+pub use panic::panic_message;
+#[cfg(feature = "std")]
+pub use partition::PartitionExt;
+pub use retry::Backoff;
+#[cfg(feature = "alloc")]
+pub use stats::ErrorStats;
+#[cfg(feature = "tracing")]
+pub use trace::TraceResultExt;
+pub use variant::VariantMatcher;
+
+/// Derives [`Classify`](trait@Classify) from `#[class(transient)]`, `#[class(permanent)]` and
+/// `#[class(unknown)]` attributes. A variant without one gets the class given on the type, or
+/// [`ErrorClass::Unknown`]. Requires the `macros` feature
 ///
 /// # Examples
 /// ```
 ///  use match_err::*;
-///  use anyhow::anyhow;
 ///
-///  #[derive(thiserror::Error, Debug)]
+///  #[derive(Debug, Classify)]
+///  enum Error {
+///     #[class(transient)]
+///     Timeout { secs: u64 },
+///     #[class(permanent)]
+///     Invalid(String),
+///     NotFound,
+///  }
+///
+///  #[derive(Debug, Classify)]
+///  #[class(transient)]
+///  struct Overloaded;
+///
+///  assert_eq!(Error::Timeout { secs: 5 }.class(), ErrorClass::Transient);
+///  assert_eq!(Error::Invalid("id".into()).class(), ErrorClass::Permanent);
+///  assert_eq!(Error::NotFound.class(), ErrorClass::Unknown);
+///  assert!(Overloaded.class().is_transient());
+/// ```
+#[cfg(feature = "macros")]
+pub use match_err_macros::Classify;
+
+/// Derives [`ErrorCode`](trait@ErrorCode) from `#[code("NOT_FOUND")]` and `#[code(404)]`
+/// attributes. A variant without one gets its name in screaming snake case, which changes when it's
+/// renamed, so codes that clients rely on should be given explicitly. Two variants with the same
+/// code fail to compile. Requires the `macros` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///
+///  #[derive(Debug, ErrorCode)]
+///  enum Error {
+///     #[code("USER_NOT_FOUND")]
+///     NotFound,
+///     #[code(1001)]
+///     Timeout { secs: u64 },
+///     RateLimited(u64),
+///  }
+///
+///  #[derive(Debug, ErrorCode)]
+///  #[code("OVERLOADED")]
+///  struct Overloaded;
+///
+///  assert_eq!(Error::NotFound.code(), Code::Str("USER_NOT_FOUND"));
+///  assert_eq!(Error::Timeout { secs: 5 }.code(), Code::Num(1001));
+///  assert_eq!(Error::RateLimited(30).code().as_str(), Some("RATE_LIMITED"));
+///  assert_eq!(Overloaded.code().to_string(), "OVERLOADED");
+/// ```
+///
+/// ```compile_fail
+///  use match_err::*;
+///
+///  #[derive(Debug, ErrorCode)]
+///  enum Error {
+///     #[code(1001)]
+///     Timeout,
+///     #[code(1001)]
+///     Unavailable,
+///  }
+/// ```
+#[cfg(feature = "macros")]
+pub use match_err_macros::ErrorCode;
+
+/// Derives `proptest::arbitrary::Arbitrary` for an error type, so property tests can generate every
+/// variant and fuzz the arms of [`match_err!`] handling them. Payloads use `any::<T>()` of their
+/// types unless a field has `#[proptest(strategy = expr)]`, variants are picked with
+/// `#[proptest(weight = n)]` relative to the default weight of 1, and `#[proptest(skip)]` leaves
+/// out variants that can't be generated. Requires the `proptest` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "proptest")]
+/// # {
+///  use match_err::*;
+///  use proptest::prelude::*;
+///  use proptest::strategy::ValueTree;
+///  use proptest::test_runner::TestRunner;
+///
+///  #[derive(thiserror::Error, ArbitraryErr, Debug)]
 ///  enum Error {
 ///     #[error("not found")]
 ///     NotFound,
 ///     #[error("custom: {0}")]
+///     #[proptest(weight = 3)]
 ///     Custom(String),
-///     #[error("tuple error")]
-///     Tuple((i32, i32))
+///     #[error("timeout after {secs}s")]
+///     Timeout {
+///         #[proptest(strategy = 1..60u64)]
+///         secs: u64,
+///     },
+///     #[error(transparent)]
+///     #[proptest(skip)]
+///     Io(#[from] std::io::Error),
 ///  }
 ///
-///  let err = anyhow!(Error::NotFound);
-///
-///  match_err!(err, Error, {
-///     NotFound => assert!(true),
-///     Custom(ref msg) => assert!(false),
-///     Tuple((x, y)) => assert!(x == y),
-///     _ => assert!(false)
+///  proptest!(|(err in any::<Error>())| {
+///     let status = match_err!(anyhow::anyhow!(err), Error, {
+///         NotFound => 404,
+///         Timeout { secs } if *secs > 30 => 504,
+///         _ => 500,
+///     });
+///     prop_assert!(status >= 400);
 ///  });
 ///
-///  match_err!(err, Error, {
-///     NotFound => assert!(true),
-///     Custom(msg) => assert!(false),
-///     Tuple((x, y)) => assert!(x == y)
-///  });
+///  let mut runner = TestRunner::deterministic();
+///  let mut generated = std::collections::HashSet::new();
+///  for _ in 0..200 {
+///     let err = any::<Error>().new_tree(&mut runner).unwrap().current();
+///     match &err {
+///         Error::Timeout { secs } => assert!((1..60).contains(secs)),
+///         Error::Io(_) => panic!("skipped variant generated"),
+///         _ => {}
+///     }
+///     generated.insert(std::mem::discriminant(&err));
+///  }
+///  assert_eq!(generated.len(), 3);
+/// # }
 /// ```
-#[macro_export]
-macro_rules! match_err {
-    ( $any:expr, $ty:ident, { $( $variant:ident $( ( $($inner:tt)+ ))? => $arm:expr ),*, _ => $default:expr } ) => (
-        if let Some(e) = $any.downcast_ref::<$ty>() {
-            match e {
-                $(
-                    $ty::$variant $( ( $($inner)+ ) )? => $arm,
-                )*
-                _ => $default
-            }
-        } else {
-            $default
-        }
-    );
-
-    ( $any:expr, $ty:ident, { $( $variant:ident $( ( $($inner:tt)+ ) )? => $arm:expr ),* $(,)? }) => (
-        match_err!($any, $ty, { $( $variant $( ( $($inner)+ ) )? => $arm ),*, _ => {} })
-    );
-}
+#[cfg(feature = "proptest")]
+pub use match_err_macros::ArbitraryErr;
 
-/// Checks if it's an error and matches the error against an enum-like error type by hiding the usage of downcast_ref method
+/// Derives accessors for every variant of an enum-like error type: `is_*` for all variants, and
+/// `as_*` and `into_*` returning the payload for tuple and struct variants. Also adds `from_any`
+/// which downcasts any [`Downcastable`] error to the type, implements [`VariantName`], and lets
+/// [`match_err!`] step into its `#[error(transparent)]` variants with `transparent(..)`. Requires
+/// the `macros` feature
 ///
 /// # Examples
 /// ```
 ///  use match_err::*;
 ///  use anyhow::anyhow;
 ///
-///  #[derive(thiserror::Error, Debug)]
+///  #[derive(thiserror::Error, MatchErr, Debug)]
 ///  enum Error {
 ///     #[error("not found")]
 ///     NotFound,
 ///     #[error("custom: {0}")]
 ///     Custom(String),
-///     #[error("tuple error")]
-///     Tuple((i32, i32))
+///     #[error("code {0}: {1}")]
+///     Code(u16, String),
+///     #[error("timeout after {secs}s")]
+///     Timeout { secs: u64 }
 ///  }
 ///
-///  let err: Result<(), _> = Err(anyhow!(Error::NotFound));
-///
-///  match_if_err!(err, Error, {
-///     NotFound => assert!(true),
-///     Custom(msg) => assert!(false),
-///     Tuple((x, y)) => assert!(x == y),
-///     _ => assert!(false)
-///  });
+///  let err = anyhow!(Error::Custom(String::from("internal")));
+///  let e = Error::from_any(&err).unwrap();
+///  assert!(e.is_custom());
+///  assert!(!e.is_not_found());
+///  assert_eq!(e.as_custom(), Some(&String::from("internal")));
+///  assert_eq!(e.as_code(), None);
 ///
-///  match_if_err!(err, Error, {
-///     NotFound => assert!(true),
-///     Custom(msg) => assert!(false),
-///     Tuple((x, y)) => assert!(x == y),
-///  });
+///  assert_eq!(Error::Code(404, String::from("gone")).into_code(), Some((404, String::from("gone"))));
+///  assert_eq!(Error::Timeout { secs: 5 }.as_timeout(), Some(&5));
+///  assert!(Error::from_any(&anyhow!("unknown")).is_none());
+///  assert_eq!(Error::Timeout { secs: 5 }.variant_name(), "Timeout");
 /// ```
-#[macro_export]
-macro_rules! match_if_err {
-    ( $any:expr, $ty:ident, { $( $variant:ident $( ( $($inner:tt)+ ) )? => $arm:expr ),*, _ => $default:expr } ) => (
-        if let Err(ref e) = $any {
-            match_err!(e, $ty, { $( $variant $( ( $($inner)+ ) )? => $arm ),*, _ => $default })
-        } else {
-            $default
-        }
-    );
-
-    ( $any:expr, $ty:ident, { $( $variant:ident $( ( $($inner:tt)+ ) )? => $arm:expr ),* $(,)? }) => (
-        match_if_err!($any, $ty, { $( $variant $( ( $($inner)+ ) )? => $arm ),*, _ => {} })
-    );
-}
+#[cfg(feature = "macros")]
+pub use match_err_macros::MatchErr;
 
-/// Asserts the variable is an error and then asserts it against an enum-like error type by hiding the usage of downcast_ref method
-/// The error is required to implement PartialEq
+/// Turns a function returning a `Result` into one that passes only if it returns an error matching
+/// the given pattern, with an optional guard. Meant for negative tests, put it above `#[test]` or
+/// `#[tokio::test]`. Requires the `macros` feature
 ///
 /// # Examples
 /// ```
 ///  use match_err::*;
 ///  use anyhow::anyhow;
 ///
-///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  #[derive(thiserror::Error, Debug)]
 ///  enum Error {
 ///     #[error("not found")]
 ///     NotFound,
 ///     #[error("custom: {0}")]
 ///     Custom(String),
-///     #[error("tuple error")]
-///     Tuple((i32, i32))
 ///  }
 ///
-///  let err: Result<(), _> = Err(anyhow!(Error::Custom(String::from("internal"))));
-///  assert_if_error!(err, Error, Custom(String::from("internal")));
+///  // #[test]
+///  #[expect_err(Error::NotFound)]
+///  fn missing_user() -> anyhow::Result<()> {
+///     Err(Error::NotFound)?;
+///     Ok(())
+///  }
 ///
-///  let err: Result<(), _> = Err(anyhow!(Error::Tuple((1,1))));
-///  assert_if_error!(err, Error, Tuple((1,1)), "error message");
+///  // #[test]
+///  #[expect_err(Error::Custom(msg) if msg.contains("disk"))]
+///  fn full_disk() -> Result<(), Box<dyn std::error::Error>> {
+///     Err(Box::new(Error::Custom(String::from("disk is full"))))
+///  }
+///
+///  missing_user();
+///  full_disk();
 /// ```
-#[macro_export]
-macro_rules! assert_if_error {
-    ($var:expr, $ty:ty, $variant:ident $( ( $inner:expr ) )?  $(, $($arg:tt)+)? ) => (
-        if let Err(ref err) = $var {
-            assert_error!(err, $ty, $variant $( ( $inner ) )?  $(, $($arg)+)? );
-        } else {
-            assert!(false, "not an error")
-        }
-    )
+///
+/// Async functions work as well, the attribute has to come before the one of the runtime
+/// ```
+///  use match_err::*;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("timeout")]
+///     Timeout,
+///  }
+///
+///  async fn fetch() -> anyhow::Result<u32> {
+///     Err(Error::Timeout.into())
+///  }
+///
+///  // #[tokio::test]
+///  #[expect_err(Error::Timeout)]
+///  async fn fetch_times_out() -> anyhow::Result<()> {
+///     fetch().await?;
+///     Ok(())
+///  }
+///
+///  block_on(fetch_times_out());
+///
+///  # fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+///  #     struct Noop;
+///  #     impl std::task::Wake for Noop { fn wake(self: std::sync::Arc<Self>) {} }
+///  #     let waker = std::sync::Arc::new(Noop).into();
+///  #     let mut cx = std::task::Context::from_waker(&waker);
+///  #     let mut fut = std::pin::pin!(fut);
+///  #     loop {
+///  #         if let std::task::Poll::Ready(out) = fut.as_mut().poll(&mut cx) { return out; }
+///  #     }
+///  # }
+/// ```
+///
+/// Succeeding or failing with another error panics
+/// ```should_panic
+///  use match_err::*;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  #[expect_err(Error::NotFound)]
+///  fn found() -> anyhow::Result<u32> {
+///     Ok(1)
+///  }
+///
+///  found();
+/// ```
+#[cfg(feature = "macros")]
+pub use match_err_macros::expect_err;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::assert::{assert_chain_failed, assert_failed, compare_with, ApproxEq};
+    #[cfg(feature = "alloc")]
+    pub use crate::assert::assert_error_failed;
+    pub use crate::downcast::{find_through, root_cause, source_at, Probe, Transparent, ViaDowncastable, ViaError};
+    pub use crate::ext::MaybeErr;
+    #[cfg(feature = "std")]
+    pub use crate::panic::AsPanicPayload;
+    #[cfg(feature = "anyhow")]
+    pub use anyhow;
+    #[cfg(feature = "alloc")]
+    pub use alloc::{format, string::String, vec::Vec};
+    pub use core::iter::IntoIterator;
+    #[cfg(feature = "http")]
+    pub use http;
+    #[cfg(feature = "insta")]
+    pub use insta;
+    #[cfg(feature = "metrics")]
+    pub use metrics;
+    #[cfg(feature = "proptest")]
+    pub use proptest;
+    #[cfg(feature = "regex")]
+    pub use regex;
+    #[cfg(feature = "tonic")]
+    pub use tonic;
+    #[cfg(feature = "alloc")]
+    pub use crate::name::{type_name, variant_name, NameProbe, ViaDebug, ViaVariantName};
+    #[cfg(feature = "tracing")]
+    pub use tracing;
+    #[cfg(feature = "macros")]
+    pub use crate::strict::{covers, Variants};
+    #[cfg(feature = "macros")]
+    pub use match_err_macros::*;
+    #[cfg(not(feature = "macros"))]
+    pub use crate::__match_err_diagnose;
 }
 
-/// Asserts the error against an enum-like error type by hiding the usage of downcast_ref method
-/// The error is required to implement PartialEq
+/// Matches the error against an enum-like error type by hiding the usage of downcast_ref method
 ///
 /// # Examples
 /// ```
 ///  use match_err::*;
 ///  use anyhow::anyhow;
 ///
-///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  #[derive(thiserror::Error, Debug)]
 ///  enum Error {
 ///     #[error("not found")]
 ///     NotFound,
 ///     #[error("custom: {0}")]
 ///     Custom(String),
 ///     #[error("tuple error")]
-///     Tuple((i32, i32))
+///     Tuple((i32, i32)),
+///     #[error("timeout after {secs}s: {op}")]
+///     Timeout { secs: u64, op: String }
 ///  }
 ///
-///  let err = anyhow!(Error::Custom(String::from("internal")));
-///  assert_error!(err, Error, Custom(String::from("internal")));
+///  let err = anyhow!(Error::NotFound);
 ///
-///  let err = anyhow!(Error::Tuple((1,1)));
-///  assert_error!(err, Error, Tuple((1,1)), "error message");
-/// ```
-#[macro_export]
-macro_rules! assert_error {
-    ($var:expr, $ty:ty, $variant:ident $( ( $inner:expr ) )? $(, $($arg:tt)+)? ) => (
-        match $var.downcast_ref::<$ty>() {
-            Some(e) if e == &<$ty>::$variant $( ( $inner ) )? => assert!(true),
-            _ => assert!(false $(, $($arg)+)? ),
-        }
-    )
+///  match_err!(err, Error, {
+///     NotFound => assert!(true),
+///     Custom(ref msg) => assert!(false),
+///     Tuple((x, y)) => assert!(x == y),
+///     _ => assert!(false)
+///  });
+///
+///  match_err!(err, Error, {
+///     NotFound => assert!(true),
+///     Custom(msg) => assert!(false),
+///     Tuple((x, y)) => assert!(x == y)
+///  });
+///
+///  let err = anyhow!(Error::Timeout { secs: 5, op: String::from("read") });
+///
+///  match_err!(err, Error, {
+///     Timeout { secs, .. } => assert_eq!(*secs, 5),
+///     _ => assert!(false)
+///  });
+///
+///  let err = anyhow!(Error::Custom(String::from("db: connection lost")));
+///
+///  match_err!(err, Error, {
+///     Custom(msg) if msg.starts_with("db:") => assert!(true),
+///     Custom(msg) => assert!(false),
+///     Timeout { secs, .. } if *secs > 10 => assert!(false),
+///     _ => assert!(false)
+///  });
+///
+///  let err = anyhow!(Error::NotFound);
+///
+///  match_err!(err, Error, {
+///     NotFound | Custom(_) => assert!(true),
+///     Tuple((x, 0)) | Tuple((0, x)) if *x > 0 => assert!(false),
+///     _ => assert!(false)
+///  });
+///
+///  mod errors {
+///     #[derive(thiserror::Error, Debug)]
+///     pub enum StorageError {
+///         #[error("corrupted block {0}")]
+///         Corrupted(u64),
+///     }
+///  }
+///
+///  let err = anyhow!(errors::StorageError::Corrupted(7));
+///
+///  match_err!(err, errors::StorageError, {
+///     Corrupted(block) => assert_eq!(*block, 7),
+///     _ => assert!(false)
+///  });
+/// ```
+///
+/// The macro is an expression, so it can produce a value as long as every arm and the `_` arm
+/// have the same type. The `_` arm is required in that case: without it the fallback is `()`
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let err = anyhow!(Error::NotFound);
+///
+///  let status = match_err!(err, Error, {
+///     NotFound => 404,
+///     Custom(_) => 400,
+///     _ => 500
+///  });
+///  assert_eq!(status, 404);
+///
+///  let status = match_err!(anyhow!("unknown"), Error, {
+///     NotFound => 404,
+///     _ => 500
+///  });
+///  assert_eq!(status, 500);
+/// ```
+///
+/// ```compile_fail
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let status: u16 = match_err!(anyhow!(Error::NotFound), Error, {
+///     NotFound => 404
+///  });
+/// ```
+///
+/// The fallback arm can bind the original error with `e @ _`, both when it isn't of the given type
+/// and when none of the variants matched. A bare `e =>` would be indistinguishable from a unit variant
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let msg = match_err!(anyhow!("connection reset"), Error, {
+///     NotFound => String::from("not found"),
+///     e @ _ => format!("unexpected: {e:#}")
+///  });
+///  assert_eq!(msg, "unexpected: connection reset");
+///
+///  let msg = match_err!(anyhow!(Error::Custom(String::from("internal"))), Error, {
+///     NotFound => String::from("not found"),
+///     e @ _ => format!("unexpected: {e:#}")
+///  });
+///  assert_eq!(msg, "unexpected: custom: internal");
+/// ```
+///
+/// Arms take outer attributes like a native `match`, such as `#[cfg(..)]` for variants that only
+/// exist on some platforms or with some features, or `#[allow(..)]` for a single arm
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[cfg(unix)]
+///     #[error("killed by signal {0}")]
+///     Signal(i32),
+///     #[error("timeout after {secs}s: {op}")]
+///     Timeout { secs: u64, op: String }
+///  }
+///
+///  let status = match_err!(anyhow!(Error::NotFound), Error, {
+///     NotFound => 404,
+///     #[cfg(unix)]
+///     Signal(_) => 500,
+///     #[allow(unused_variables)]
+///     Timeout { secs, op } => 504,
+///     _ => 500
+///  });
+///  assert_eq!(status, 404);
+/// ```
+///
+/// Payloads accept any pattern of a native `match`, including literals, constants and `_`.
+/// Note that string literals only match `&str` payloads, a `String` needs a guard
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("status {0}")]
+///     Status(u16),
+///     #[error("reason: {0}")]
+///     Reason(&'static str),
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  const GONE: u16 = 410;
+///
+///  let describe = |err: anyhow::Error| match_err!(err, Error, {
+///     Status(404) => "not found",
+///     Status(GONE) => "gone",
+///     Status(_) => "other status",
+///     Reason("timeout") => "timeout",
+///     Custom(msg) if msg == "gone" => "gone",
+///     _ => "unknown"
+///  });
+///
+///  assert_eq!(describe(anyhow!(Error::Status(404))), "not found");
+///  assert_eq!(describe(anyhow!(Error::Status(410))), "gone");
+///  assert_eq!(describe(anyhow!(Error::Status(500))), "other status");
+///  assert_eq!(describe(anyhow!(Error::Reason("timeout"))), "timeout");
+///  assert_eq!(describe(anyhow!(Error::Custom(String::from("gone")))), "gone");
+///  assert_eq!(describe(anyhow!(Error::Reason("other"))), "unknown");
+/// ```
+///
+/// Fields that don't matter can be skipped with `..`
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::path::PathBuf;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("io error at {0:?}: {1}")]
+///     Io(PathBuf, std::io::Error, u32),
+///     #[error("timeout after {secs}s: {op}")]
+///     Timeout { secs: u64, op: String }
+///  }
+///
+///  let err = anyhow!(Error::Io(PathBuf::from("/tmp/db"), std::io::Error::other("broken pipe"), 3));
+///
+///  match_err!(err, Error, {
+///     Io(..) => assert!(true),
+///     _ => assert!(false)
+///  });
+///
+///  match_err!(err, Error, {
+///     Io(path, ..) => assert_eq!(path, &PathBuf::from("/tmp/db")),
+///     Timeout { .. } => assert!(false),
+///  });
+///
+///  match_err!(err, Error, {
+///     Io(.., retries) if *retries > 5 => assert!(false),
+///     Io(_, ..) => assert!(true),
+///     _ => assert!(false)
+///  });
+/// ```
+///
+/// Payloads can be tested and bound at the same time with `@`
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("code {0}")]
+///     Code(u16),
+///     #[error("timeout after {secs}s")]
+///     Timeout { secs: u64 }
+///  }
+///
+///  let describe = |err: anyhow::Error| match_err!(err, Error, {
+///     Code(c @ 500..=599) => format!("server error {c}"),
+///     Code(c @ (400 | 404)) => format!("client error {c}"),
+///     Timeout { secs: s @ 1..=10 } => format!("short timeout {s}"),
+///     _ => String::from("other")
+///  });
+///
+///  assert_eq!(describe(anyhow!(Error::Code(503))), "server error 503");
+///  assert_eq!(describe(anyhow!(Error::Code(404))), "client error 404");
+///  assert_eq!(describe(anyhow!(Error::Code(200))), "other");
+///  assert_eq!(describe(anyhow!(Error::Timeout { secs: 5 })), "short timeout 5");
+///
+///  assert!(matches_err!(anyhow!(Error::Code(503)), Error, Code(c @ 500..=599) if *c != 501));
+///  assert_err_matches!(anyhow!(Error::Code(503)), Error::Code(c @ 500..=599) if *c == 503);
+/// ```
+///
+/// Payloads holding other enums can be matched with nested patterns in the same arm,
+/// only the outer variant is prefixed with the error type
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(Debug)]
+///  enum TransportError {
+///     Timeout,
+///     Refused { port: u16 }
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("transport: {0:?}")]
+///     Transport(TransportError),
+///     #[error("retries: {0:?}")]
+///     Retries(Option<u32>)
+///  }
+///
+///  let describe = |err: anyhow::Error| match_err!(err, Error, {
+///     Transport(TransportError::Timeout) => String::from("timeout"),
+///     Transport(TransportError::Refused { port: 80 | 443 }) => String::from("web refused"),
+///     Transport(TransportError::Refused { port }) => format!("refused {port}"),
+///     Retries(Some(n @ 1..)) => format!("{n} retries"),
+///     _ => String::from("other")
+///  });
+///
+///  assert_eq!(describe(anyhow!(Error::Transport(TransportError::Timeout))), "timeout");
+///  assert_eq!(describe(anyhow!(Error::Transport(TransportError::Refused { port: 443 }))), "web refused");
+///  assert_eq!(describe(anyhow!(Error::Transport(TransportError::Refused { port: 22 }))), "refused 22");
+///  assert_eq!(describe(anyhow!(Error::Retries(Some(3)))), "3 retries");
+///  assert_eq!(describe(anyhow!(Error::Retries(None))), "other");
+/// ```
+///
+/// Generic error types take their arguments with or without a turbofish, in this and the other macros
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum StoreError<K: std::fmt::Debug + Send + Sync + 'static> {
+///     #[error("{0:?} not found")]
+///     NotFound(K),
+///     #[error("corrupted")]
+///     Corrupted,
+///  }
+///
+///  let err = anyhow!(StoreError::NotFound(42u64));
+///
+///  let missing = match_err!(err, StoreError<u64>, { NotFound(id) => Some(*id), _ => None });
+///  assert_eq!(missing, Some(42));
+///
+///  let missing = match_err!(err, StoreError::<String>, { NotFound(id) => Some(id.clone()), _ => None });
+///  assert_eq!(missing, None);
+///
+///  assert!(matches_err!(err, StoreError<u64>, NotFound(_)));
+/// ```
+///
+/// `deep` searches the whole cause chain like [`match_err_chain!`], and `depth = n` matches only the
+/// error `n` levels down the chain, `0` being the error itself
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum ApiError {
+///     #[error("upstream failed")]
+///     Upstream(#[source] Error),
+///  }
+///
+///  let err = anyhow!(ApiError::Upstream(Error::NotFound)).context("handling request");
+///
+///  assert!(!match_err!(err, Error, { NotFound => true, _ => false }));
+///  assert!(match_err!(deep err, Error, { NotFound => true, _ => false }));
+///  assert!(match_err!(depth = 2 err, Error, { NotFound => true, _ => false }));
+///  assert!(!match_err!(depth = 1 err, Error, { NotFound => true, _ => false }));
+///  assert!(!match_err!(depth = 3 err, Error, { NotFound => true, _ => false }));
+/// ```
+///
+/// `deep` also unwraps the layers that hide the error from the cause chain: an `anyhow::Error`
+/// nested in another one with `anyhow::Error::msg`, a `Box<dyn Error + Send + Sync>` turned into one
+/// with `anyhow!`, and an error wrapped in a `Box` or an `Arc`. It doesn't step into
+/// `#[error(transparent)]` variants though: it only follows `source()`, and thiserror's transparent
+/// `source()` skips the wrapped error. Those need their wrapper types listed with `transparent(..)`
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::sync::Arc;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  #[error("upstream failed")]
+///  struct Upstream(#[source] Arc<Error>);
+///
+///  let err = anyhow::Error::msg(anyhow!(Error::NotFound).context("loading user"));
+///  assert!(!match_err!(err, Error, { NotFound => true, _ => false }));
+///  assert!(match_err!(deep err, Error, { NotFound => true, _ => false }));
+///
+///  let boxed: Box<dyn std::error::Error + Send + Sync> = Box::new(Error::NotFound);
+///  let err = anyhow!(boxed);
+///  assert!(!match_err!(err, Error, { NotFound => true, _ => false }));
+///  assert!(match_err!(deep err, Error, { NotFound => true, _ => false }));
+///
+///  let err = anyhow!(Upstream(Arc::new(Error::NotFound)));
+///  assert!(match_err!(deep err, Error, { NotFound => true, _ => false }));
+/// ```
+///
+/// The `source()` of an `#[error(transparent)]` variant skips the error it wraps, so it can't be
+/// found in the cause chain, even with `deep`. Wrapper types deriving [`MatchErr`] can be listed
+/// with `transparent(..)` to step into those variants while the chain is searched. Only the listed
+/// types are stepped into, and each of them must derive [`MatchErr`], since nothing else exposes
+/// the error a transparent variant wraps
+///
+/// ```
+/// # #[cfg(feature = "macros")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum StoreError {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("corrupted")]
+///     Corrupted,
+///  }
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum ServiceError {
+///     #[error(transparent)]
+///     Store(#[from] StoreError),
+///     #[error(transparent)]
+///     Other(#[from] anyhow::Error),
+///  }
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum AppError {
+///     #[error("service failed")]
+///     Service(#[source] ServiceError),
+///  }
+///
+///  let status = |err: anyhow::Error| match_err!(transparent(AppError, ServiceError) err, StoreError, {
+///     NotFound => 404,
+///     Corrupted => 500,
+///     _ => 502
+///  });
+///
+///  assert_eq!(status(anyhow!(ServiceError::Store(StoreError::NotFound))), 404);
+///  assert_eq!(status(anyhow!(AppError::Service(ServiceError::Store(StoreError::Corrupted)))), 500);
+///  assert_eq!(status(anyhow!(ServiceError::Other(anyhow!(StoreError::NotFound)))), 404);
+///  assert_eq!(status(anyhow!(ServiceError::Other(anyhow!("unknown")))), 502);
+///
+///  let status = match_err!(anyhow!(ServiceError::Store(StoreError::NotFound)), StoreError, {
+///     NotFound => 404,
+///     _ => 502
+///  });
+///  assert_eq!(status, 502);
+/// # }
+/// ```
+///
+/// Invalid input is reported at the mistake, like a missing comma between two arms or a variant
+/// written with the path of its type, which the macro prepends itself
+/// ```compile_fail
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  // error: the error type is prepended to the variants, write `NotFound` instead of `Error::NotFound`
+///  let status = match_err!(anyhow!(Error::NotFound), Error, {
+///     Error::NotFound => 404,
+///     _ => 500
+///  });
+/// ```
+#[macro_export]
+macro_rules! match_err {
+    (@find top $any:ident $ty:tt) => ({
+        #[allow(unused_imports)]
+        use $crate::__private::{ViaDowncastable as _, ViaError as _};
+        (&$crate::__private::Probe($any)).match_err_downcast::<$crate::__match_err_path!($ty)>()
+    });
+
+    (@find chain $any:ident $ty:tt) => ({
+        #[allow(unused_imports)]
+        use $crate::__private::{ViaDowncastable as _, ViaError as _};
+        (&$crate::__private::Probe($any)).match_err_find::<$crate::__match_err_path!($ty)>()
+    });
+
+    (@find root $any:ident $ty:tt) => ({
+        #[allow(unused_imports)]
+        use $crate::__private::{ViaDowncastable as _, ViaError as _};
+        $crate::__private::root_cause((&$crate::__private::Probe($any)).match_err_dyn())
+            .and_then(|e| e.downcast_ref::<$crate::__match_err_path!($ty)>())
+    });
+
+    (@find [depth $depth:literal] $any:ident $ty:tt) => ({
+        #[allow(unused_imports)]
+        use $crate::__private::{ViaDowncastable as _, ViaError as _};
+        $crate::__private::source_at((&$crate::__private::Probe($any)).match_err_dyn(), $depth)
+            .and_then(|e| e.downcast_ref::<$crate::__match_err_path!($ty)>())
+    });
+
+    (@find [transparent $( [$($wrapper:tt)*] )+] $any:ident $ty:tt) => ({
+        #[allow(unused_imports)]
+        use $crate::__private::{ViaDowncastable as _, ViaError as _};
+        fn unwrap<'e>(e: &'e (dyn ::core::error::Error + 'static)) -> ::core::option::Option<&'e (dyn ::core::error::Error + 'static)> {
+            $(
+                if let ::core::option::Option::Some(wrapper) = e.downcast_ref::<$($wrapper)*>() {
+                    return $crate::__private::Transparent::transparent(wrapper);
+                }
+            )+
+            ::core::option::Option::None
+        }
+        let probe = &$crate::__private::Probe($any);
+        probe
+            .match_err_find::<$crate::__match_err_path!($ty)>()
+            .or_else(|| $crate::__private::find_through(probe.match_err_dyn(), unwrap))
+    });
+
+    (@match mutable $any:tt $ty:tt [$($arms:tt)*] [$($bind:ident)?] $default:expr) => (
+        match &mut $any {
+            any => if let Some(e) = $crate::DowncastMut::downcast_mut::<$crate::__match_err_path!($ty)>(any) {
+                $crate::__match_err_hook!(&*e);
+                match e {
+                    $($arms)*
+                    _ => { $( let $bind = any; )? $default }
+                }
+            } else {
+                $( let $bind = any; )?
+                $default
+            }
+        }
+    );
+
+    (@match owned $any:tt $ty:tt [$($arms:tt)*] [] $default:expr) => (
+        match $crate::DowncastOwned::downcast::<$crate::__match_err_path!($ty)>($any) {
+            Ok(e) => {
+                $crate::__match_err_hook!(&e);
+                match e {
+                    $($arms)*
+                    _ => $default
+                }
+            },
+            Err(_) => $default
+        }
+    );
+
+    (@match owned $any:tt $ty:tt [$($arms:tt)*] [$bind:ident] $default:expr) => (
+        compile_error!("match_err_owned! consumes the error, use `_` as the fallback arm")
+    );
+
+    (@match $find:tt $any:tt $ty:tt [$($arms:tt)*] [$($bind:ident)?] $default:expr) => (
+        match &$any {
+            any => if let Some(e) = $crate::match_err!(@find $find any $ty) {
+                $crate::__match_err_hook!(e);
+                match e {
+                    $($arms)*
+                    _ => { $( let $bind = any; )? $default }
+                }
+            } else {
+                $( let $bind = any; )?
+                $default
+            }
+        }
+    );
+
+    (@arms $find:tt $any:tt $ty:tt [$($arms:tt)*] _ => $default:expr $(,)?) => (
+        $crate::match_err!(@match $find $any $ty [$($arms)*] [] $default)
+    );
+
+    (@arms $find:tt $any:tt $ty:tt [$($arms:tt)*] $bind:ident @ _ => $default:expr $(,)?) => (
+        $crate::match_err!(@match $find $any $ty [$($arms)*] [$bind] $default)
+    );
+
+    (@arms $find:tt $any:tt $ty:tt [$($arms:tt)*] $(,)?) => (
+        $crate::match_err!(@match $find $any $ty [$($arms)*] [] {})
+    );
+
+    (@arms $find:tt $any:tt $ty:tt [$($arms:tt)*] $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_err!(@arms $find $any $ty [
+            $($arms)*
+            $( #[$attr] )* $( $crate::__match_err_path!($ty $variant $( ( $($inner)+ ) )? $( { $($fields)* } )?) )|+ $( if $guard )? => $arm,
+        ] $($($rest)*)?)
+    );
+
+    (@arms $find:tt $any:tt $ty:tt [$($arms:tt)*] $($rest:tt)*) => (
+        $crate::__private::__match_err_diagnose!($crate; arms $($rest)*)
+    );
+
+    ( deep $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::match_err!(@arms chain $any [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*)
+    );
+
+    ( depth = $depth:literal $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::match_err!(@arms [depth $depth] $any [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*)
+    );
+
+    ( transparent ( $( $($wrapper:ident)::+ $(::)? $(< $($wgen:ty),+ $(,)? >)? ),+ $(,)? ) $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::match_err!(@arms [transparent $( [$($wrapper)::+ $(::<$($wgen),+>)?] )+] $any [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*)
+    );
+
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::match_err!(@arms top $any [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*)
+    );
+
+    ( $($input:tt)* ) => (
+        $crate::__private::__match_err_diagnose!($crate; input $($input)*)
+    );
+}
+
+/// Matches the first error of an enum-like error type found anywhere in the cause chain,
+/// so errors wrapped as the source of other errors match as well. The arms are the same as in [`match_err!`]
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum ApiError {
+///     #[error("upstream failed")]
+///     Upstream(#[source] Error),
+///  }
+///
+///  let err = anyhow!(ApiError::Upstream(Error::Custom(String::from("internal"))))
+///     .context("handling request");
+///
+///  match_err!(err, Error, {
+///     Custom(_) => assert!(false),
+///     _ => assert!(true)
+///  });
+///
+///  match_err_chain!(err, Error, {
+///     NotFound => assert!(false),
+///     Custom(msg) => assert_eq!(msg, "internal"),
+///     _ => assert!(false)
+///  });
+///
+///  let err = anyhow!(Error::NotFound).context("loading user");
+///  let found = match_err_chain!(err, Error, { NotFound => true, _ => false });
+///  assert!(found);
+/// ```
+#[macro_export]
+macro_rules! match_err_chain {
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::match_err!(@arms chain $any [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*)
+    );
+}
+
+/// Matches the root cause of the error, the last error of its `source()` chain, against an
+/// enum-like error type. The arms are the same as in [`match_err!`]
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum ApiError {
+///     #[error("upstream failed")]
+///     Upstream(#[source] Error),
+///  }
+///
+///  let root = |err: anyhow::Error| match_root_err!(err, Error, { NotFound => true, _ => false });
+///
+///  assert!(root(anyhow!(ApiError::Upstream(Error::NotFound)).context("handling request")));
+///  assert!(root(anyhow!(Error::NotFound)));
+///  assert!(!root(anyhow!("unknown")));
+/// ```
+#[macro_export]
+macro_rules! match_root_err {
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::match_err!(@arms root $any [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*)
+    );
+}
+
+/// Matches the error against an enum-like error type like [`match_err!`], but consumes the error
+/// so the arms get owned payloads. The error is dropped when no arm matches, hence the fallback
+/// can't bind it
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("timeout after {secs}s")]
+///     Timeout { secs: u64 }
+///  }
+///
+///  fn take_ownership(msg: String) -> String {
+///     msg
+///  }
+///
+///  let err = anyhow!(Error::Custom(String::from("boom")));
+///  let msg = match_err_owned!(err, Error, {
+///     Custom(msg) => take_ownership(msg),
+///     Timeout { secs } if secs > 5 => format!("slow: {secs}"),
+///     _ => String::new()
+///  });
+///  assert_eq!(msg, "boom");
+///
+///  let err: Box<dyn std::error::Error + Send + Sync> = Box::new(Error::NotFound);
+///  let found = match_err_owned!(err, Error, { NotFound => true, _ => false });
+///  assert!(found);
+/// ```
+///
+/// The error isn't available in the fallback arm
+/// ```compile_fail
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  match_err_owned!(anyhow!("unknown"), Error, {
+///     NotFound => {},
+///     e @ _ => drop(e)
+///  });
+/// ```
+#[macro_export]
+macro_rules! match_err_owned {
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::match_err!(@arms owned ($any) [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*)
+    );
+}
+
+/// Matches the error against an enum-like error type like [`match_err!`] through a mutable reference,
+/// so the payloads can be changed in place. Bindings are `ref mut` without spelling it out
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("login failed for {user} with {password}")]
+///     Login { user: String, password: String },
+///     #[error("retried {0} times")]
+///     Retried(u32)
+///  }
+///
+///  let mut err = anyhow!(Error::Login { user: "admin".into(), password: "hunter2".into() });
+///  match_err_mut!(err, Error, {
+///     Login { password, .. } => *password = "***".into(),
+///     Retried(count) => *count = 0,
+///     _ => {}
+///  });
+///  assert_eq!(err.to_string(), "login failed for admin with ***");
+///
+///  let mut err = anyhow!("unknown");
+///  match_err_mut!(err, Error, {
+///     Retried(count) => *count += 1,
+///     e @ _ => *e = anyhow!("redacted: {e}")
+///  });
+///  assert_eq!(err.to_string(), "redacted: unknown");
+/// ```
+#[macro_export]
+macro_rules! match_err_mut {
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::match_err!(@arms mutable $any [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*)
+    );
+}
+
+/// Tries several error types in order with a single fallback. A type followed by a closure-like
+/// `|e| ...` gets a reference to the error, an enum-like type followed by a block takes the arms of
+/// [`match_err!`]. Variants not handled by a block fall through to the next entries, and the final
+/// fallback is evaluated once at most
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::io;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("timeout")]
+///     Timeout
+///  }
+///
+///  let describe = |err: anyhow::Error| match_err_types!(err, {
+///     io::Error => |e| format!("io: {:?}", e.kind()),
+///     std::fmt::Error => |_| String::from("fmt"),
+///     Error {
+///        NotFound => String::from("not found"),
+///        Custom(msg) if !msg.is_empty() => format!("custom: {msg}")
+///     },
+///     e @ _ => format!("other: {e}")
+///  });
+///
+///  assert_eq!(describe(anyhow!(io::Error::from(io::ErrorKind::NotFound))), "io: NotFound");
+///  assert_eq!(describe(anyhow!(std::fmt::Error)), "fmt");
+///  assert_eq!(describe(anyhow!(Error::NotFound)), "not found");
+///  assert_eq!(describe(anyhow!(Error::Custom(String::from("boom")))), "custom: boom");
+///  assert_eq!(describe(anyhow!(Error::Timeout)), "other: timeout");
+///  assert_eq!(describe(anyhow!("unknown")), "other: unknown");
+/// ```
+///
+/// A block can handle its remaining variants itself with `_`
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::io;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("timeout")]
+///     Timeout
+///  }
+///
+///  let code = match_err_types!(anyhow!(Error::Timeout), {
+///     Error { NotFound => 404, _ => 500 },
+///     io::Error => |_| 503,
+///     _ => 0
+///  });
+///  assert_eq!(code, 500);
+/// ```
+///
+/// Layered errors compose, since an error with context downcasts to both the context and the
+/// error. Unmatched variants of the outer layer fall through into the block of the inner one
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum DbError {
+///     #[error("locked")]
+///     Locked,
+///     #[error("corrupted")]
+///     Corrupted
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum CacheError {
+///     #[error("miss")]
+///     Miss,
+///     #[error("stale")]
+///     Stale
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum ApiError {
+///     #[error("unauthorized")]
+///     Unauthorized,
+///     #[error("failed")]
+///     Failed
+///  }
+///
+///  let mut fallbacks = 0;
+///  let mut status = |err: anyhow::Error| match_err_types!(err, {
+///     ApiError { Unauthorized => 401 },
+///     CacheError { Stale => 409 },
+///     DbError { Locked => 423 },
+///     _ => { fallbacks += 1; 500 }
+///  });
+///
+///  assert_eq!(status(anyhow!(DbError::Locked).context(CacheError::Miss).context(ApiError::Failed)), 423);
+///  assert_eq!(status(anyhow!(DbError::Locked).context(CacheError::Stale).context(ApiError::Failed)), 409);
+///  assert_eq!(status(anyhow!(DbError::Locked).context(ApiError::Unauthorized)), 401);
+///  assert_eq!(status(anyhow!(DbError::Corrupted).context(CacheError::Miss)), 500);
+///  assert_eq!(fallbacks, 1);
+/// ```
+#[macro_export]
+macro_rules! match_err_types {
+    (@expand $label:lifetime $any:ident ;) => ();
+
+    (@expand $label:lifetime $any:ident ; (fn [$($ty:tt)*] ($e:pat_param) ($body:expr)) $($rest:tt)*) => (
+        if let Some(e) = $crate::__match_err_probe!(match_err_downcast $any, $($ty)*) {
+            $crate::__match_err_hook!(e);
+            let $e = e;
+            break $label $body;
+        }
+        $crate::match_err_types!(@expand $label $any ; $($rest)*)
+    );
+
+    (@expand $label:lifetime $any:ident ; (enum [$($ty:tt)*] { $($arms:tt)* }) $($rest:tt)*) => (
+        if let Some(e) = $crate::__match_err_probe!(match_err_downcast $any, $($ty)*) {
+            $crate::__match_err_hook!(e);
+            $crate::match_err_types!(@arms $label $any e [$($ty)*] [] $($arms)*)
+        }
+        $crate::match_err_types!(@expand $label $any ; $($rest)*)
+    );
+
+    (@arms $label:lifetime $any:ident $e:ident $ty:tt [$($arms:tt)*] _ => $own:expr $(,)?) => (
+        match $e {
+            $($arms)*
+            _ => break $label $own
+        }
+    );
+
+    (@arms $label:lifetime $any:ident $e:ident $ty:tt [$($arms:tt)*] $bind:ident @ _ => $own:expr $(,)?) => (
+        match $e {
+            $($arms)*
+            _ => { let $bind = $any; break $label $own }
+        }
+    );
+
+    (@arms $label:lifetime $any:ident $e:ident $ty:tt [$($arms:tt)*] $(,)?) => (
+        match $e {
+            $($arms)*
+            _ => {}
+        }
+    );
+
+    (@arms $label:lifetime $any:ident $e:ident $ty:tt [$($arms:tt)*] $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_err_types!(@arms $label $any $e $ty [
+            $($arms)*
+            $( #[$attr] )* $( $crate::__match_err_path!($ty $variant $( ( $($inner)+ ) )? $( { $($fields)* } )?) )|+ $( if $guard )? => break $label $arm,
+        ] $($($rest)*)?)
+    );
+
+    (@match $any:tt [$($entries:tt)*] [$($bind:ident)?] $default:expr) => (
+        match &$any {
+            any => 'matched: {
+                $crate::match_err_types!(@expand 'matched any ; $($entries)*);
+                $( let $bind = any; )?
+                $default
+            }
+        }
+    );
+
+    (@entries $any:tt [$($entries:tt)*] _ => $default:expr $(,)?) => (
+        $crate::match_err_types!(@match $any [$($entries)*] [] $default)
+    );
+
+    (@entries $any:tt [$($entries:tt)*] $bind:ident @ _ => $default:expr $(,)?) => (
+        $crate::match_err_types!(@match $any [$($entries)*] [$bind] $default)
+    );
+
+    (@entries $any:tt [$($entries:tt)*] $(,)?) => (
+        $crate::match_err_types!(@match $any [$($entries)*] [] {})
+    );
+
+    (@entries $any:tt [$($entries:tt)*] $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)? => |$e:pat_param| $body:expr $(, $($rest:tt)*)?) => (
+        $crate::match_err_types!(@entries $any [$($entries)* (fn [$($ty)::+ $(::<$($gen),+>)?] ($e) ($body))] $($($rest)*)?)
+    );
+
+    (@entries $any:tt [$($entries:tt)*] $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)? { $($arms:tt)* } $(, $($rest:tt)*)?) => (
+        $crate::match_err_types!(@entries $any [$($entries)* (enum [$($ty)::+ $(::<$($gen),+>)?] { $($arms)* })] $($($rest)*)?)
+    );
+
+    ( $any:expr, { $($entries:tt)* } ) => (
+        $crate::match_err_types!(@entries $any [] $($entries)*)
+    );
+}
+
+/// Matches the error against an enum-like error type like [`match_err!`], but checks the arms at
+/// compile time. Unknown variants are reported at the arm they're used in, and all variants have to be
+/// covered since the `_` arm only handles errors of other types. Requires the `macros` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("timeout after {secs}s")]
+///     Timeout { secs: u64 }
+///  }
+///
+///  let err = anyhow!(Error::Timeout { secs: 5 });
+///
+///  let status = match_err_exhaustive!(err, Error, {
+///     NotFound => 404,
+///     Custom(msg) if msg.is_empty() => 400,
+///     Custom(_) => 500,
+///     Timeout { secs } => 500 + *secs,
+///     _ => 500
+///  });
+///  assert_eq!(status, 505);
+///
+///  match_err_exhaustive!(anyhow!("unknown"), Error, {
+///     NotFound | Custom(_) | Timeout { .. } => assert!(false),
+///     e @ _ => assert_eq!(e.to_string(), "unknown")
+///  });
+/// ```
+///
+/// Nested enums are checked as well, each of their variants has to be covered
+/// ```compile_fail
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(Debug)]
+///  enum TransportError {
+///     Timeout,
+///     Refused,
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("transport: {0:?}")]
+///     Transport(TransportError),
+///  }
+///
+///  match_err_exhaustive!(anyhow!(Error::Transport(TransportError::Timeout)), Error, {
+///     Transport(TransportError::Timeout) => {}
+///  });
+/// ```
+///
+/// A missing variant doesn't compile
+/// ```compile_fail
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  match_err_exhaustive!(anyhow!(Error::NotFound), Error, {
+///     NotFound => {}
+///  });
+/// ```
+///
+/// Neither does a misspelled one
+/// ```compile_fail
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  match_err_exhaustive!(anyhow!(Error::NotFound), Error, {
+///     NotFund => {}
+///  });
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! match_err_exhaustive {
+    ($($input:tt)*) => (
+        $crate::__private::__match_err_exhaustive!($crate; $($input)*)
+    );
+}
+
+/// Matches the error against an enum-like error type like [`match_err!`], but expands in a single
+/// procedural macro that keeps the tokens of the arms as they are written. IDEs like rust-analyzer
+/// see through it, so completion, go-to-definition and renaming work for the variants and the
+/// bindings in the arms. Requires the `macros` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("timeout after {secs}s")]
+///     Timeout { secs: u64 },
+///  }
+///
+///  let describe = |err: anyhow::Error| match_err_spanned!(err, Error, {
+///     NotFound => String::from("not found"),
+///     Custom(msg) if msg.is_empty() => String::from("custom"),
+///     Custom(msg) => format!("custom: {msg}"),
+///     Timeout { secs } if *secs > 60 => format!("slow: {secs}"),
+///     e @ _ => format!("unexpected: {e}")
+///  });
+///
+///  assert_eq!(describe(anyhow!(Error::NotFound)), "not found");
+///  assert_eq!(describe(anyhow!(Error::Custom(String::from("disk")))), "custom: disk");
+///  assert_eq!(describe(anyhow!(Error::Timeout { secs: 90 })), "slow: 90");
+///  assert_eq!(describe(anyhow!(Error::Timeout { secs: 5 })), "unexpected: timeout after 5s");
+///  assert_eq!(describe(anyhow!("unknown")), "unexpected: unknown");
+///
+///  let err: Box<dyn std::error::Error> = Box::new(Error::NotFound);
+///  let mut found = false;
+///  match_err_spanned!(err, Error, { NotFound => found = true });
+///  assert!(found);
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! match_err_spanned {
+    ($($input:tt)*) => (
+        $crate::__private::__match_err_spanned!($crate; $($input)*)
+    );
+}
+
+/// Matches the error against an enum-like error type like [`match_err!`], but the `_` arm can't be
+/// left out, and it warns with a `deprecated` warning when `_` would swallow variants that aren't
+/// listed. The type has to derive [`MatchErr`]. Requires the `macros` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let status = |err: anyhow::Error| match_err_strict!(err, Error, {
+///     NotFound => 404,
+///     Custom(msg) if msg.is_empty() => 400,
+///     Custom(_) => 422,
+///     _ => 500
+///  });
+///  assert_eq!(status(anyhow!(Error::NotFound)), 404);
+///  assert_eq!(status(anyhow!(Error::Custom("id".into()))), 422);
+///  assert_eq!(status(anyhow!("unknown")), 500);
+/// ```
+///
+/// A missing `_` arm doesn't compile
+/// ```compile_fail
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  match_err_strict!(anyhow!(Error::NotFound), Error, {
+///     NotFound => {}
+///  });
+/// ```
+///
+/// And `_` swallowing `Custom` warns
+/// ```compile_fail
+///  #![deny(deprecated)]
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  match_err_strict!(anyhow!(Error::NotFound), Error, {
+///     NotFound => {},
+///     _ => {}
+///  });
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! match_err_strict {
+    ($($input:tt)*) => (
+        $crate::__private::__match_err_strict!($crate; $($input)*)
+    );
+}
+
+/// Checks if it's an error and matches the error against an enum-like error type by hiding the usage of downcast_ref method
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("tuple error")]
+///     Tuple((i32, i32)),
+///     #[error("timeout after {secs}s: {op}")]
+///     Timeout { secs: u64, op: String }
+///  }
+///
+///  let err: Result<(), _> = Err(anyhow!(Error::NotFound));
+///
+///  match_if_err!(err, Error, {
+///     NotFound => assert!(true),
+///     Custom(msg) => assert!(false),
+///     Tuple((x, y)) => assert!(x == y),
+///     _ => assert!(false)
+///  });
+///
+///  match_if_err!(err, Error, {
+///     NotFound => assert!(true),
+///     Custom(msg) => assert!(false),
+///     Tuple((x, y)) => assert!(x == y),
+///  });
+///
+///  let err: Result<(), _> = Err(anyhow!(Error::Timeout { secs: 5, op: String::from("read") }));
+///
+///  match_if_err!(err, Error, {
+///     Timeout { secs, op } => assert_eq!((*secs, op.as_str()), (5, "read")),
+///     _ => assert!(false)
+///  });
+///
+///  match_if_err!(err, Error, {
+///     Timeout { secs, .. } if *secs > 10 => assert!(false),
+///     Timeout { .. } => assert!(true),
+///     NotFound if true => assert!(false),
+///  });
+///
+///  match_if_err!(err, Error, {
+///     NotFound | Custom(_) => assert!(false),
+///     Tuple(_) | Timeout { .. } => assert!(true),
+///  });
+///
+///  mod errors {
+///     #[derive(thiserror::Error, Debug)]
+///     pub enum StorageError {
+///         #[error("corrupted block {0}")]
+///         Corrupted(u64),
+///     }
+///  }
+///
+///  let err: Result<(), _> = Err(anyhow!(errors::StorageError::Corrupted(7)));
+///
+///  match_if_err!(err, errors::StorageError, {
+///     Corrupted(block) => assert_eq!(*block, 7),
+///  });
+/// ```
+///
+/// Like [`match_err!`] it can be used in expression position, and the `_` arm is then required.
+/// The `Ok` case evaluates to the `_` arm as well
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let res: Result<(), _> = Err(anyhow!(Error::NotFound));
+///  let status = match_if_err!(res, Error, { NotFound => 404, _ => 200 });
+///  assert_eq!(status, 404);
+///
+///  let res: Result<(), anyhow::Error> = Ok(());
+///  let status = match_if_err!(res, Error, { NotFound => 404, _ => 200 });
+///  assert_eq!(status, 200);
+/// ```
+///
+/// ```compile_fail
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let res: Result<(), _> = Err(anyhow!(Error::NotFound));
+///  let status: u16 = match_if_err!(res, Error, { NotFound => 404 });
+/// ```
+///
+/// An `Ok(..)` arm handles the success value in the same construct. With it, the fallback arm
+/// can also bind the original error with `e @ _`
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let res: Result<u32, anyhow::Error> = Ok(42);
+///  let value = match_if_err!(res, Error, {
+///     Ok(v) => v,
+///     NotFound => 0,
+///     _ => unreachable!()
+///  });
+///  assert_eq!(value, 42);
+///
+///  let res: Result<u32, _> = Err(anyhow!("connection reset"));
+///  let msg = match_if_err!(res, Error, {
+///     Ok(v) => v.to_string(),
+///     NotFound => String::from("not found"),
+///     e @ _ => format!("unexpected: {e}")
+///  });
+///  assert_eq!(msg, "unexpected: connection reset");
+/// ```
+///
+/// The error doesn't have to be type-erased, results of the error type itself and of plain
+/// errors like `std::io::Error` are matched the same way
+///
+/// ```
+///  use match_err::*;
+///  use std::io;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let res: Result<(), Error> = Err(Error::NotFound);
+///  assert_eq!(match_if_err!(res, Error, { NotFound => 404, _ => 500 }), 404);
+///
+///  let res: Result<(), io::Error> = Err(io::Error::other("disk"));
+///  assert_eq!(match_if_err!(res, Error, { NotFound => 404, _ => 500 }), 500);
+///
+///  let res: Result<(), Box<dyn std::error::Error>> = Err(Box::new(Error::NotFound));
+///  assert_eq!(match_if_err!(res, Error, { NotFound => 404, _ => 500 }), 404);
+///
+///  let res: Result<(), Upstream> = Err(Upstream(Error::NotFound));
+///  assert_eq!(match_err_chain!(res.unwrap_err(), Error, { NotFound => 404, _ => 500 }), 404);
+///
+///  #[derive(thiserror::Error, Debug)]
+///  #[error("upstream failed")]
+///  struct Upstream(#[source] Error);
+/// ```
+///
+/// An `Option` of an error is taken as well, with `None` handled like `Ok`. The `Ok(..)` arm
+/// needs a `Result`
+///
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("timeout")]
+///     Timeout,
+///  }
+///
+///  let outcome = |failure: Option<anyhow::Error>| match_if_err!(failure, Error, {
+///     Timeout => "retry",
+///     _ => "done"
+///  });
+///  assert_eq!(outcome(Some(anyhow!(Error::Timeout))), "retry");
+///  assert_eq!(outcome(Some(anyhow!("unknown"))), "done");
+///  assert_eq!(outcome(None), "done");
+/// ```
+///
+/// A `std::thread::Result` of joining a thread is taken without the error type, the arms other
+/// than `Ok(..)` then match the panic payload like [`match_panic!`]. Requires the `std` feature
+///
+/// ```
+///  use match_err::*;
+///
+///  let join = |f: fn() -> u32| match_if_err!(std::thread::spawn(f).join(), {
+///     Ok(n) => format!("got {n}"),
+///     &str => |msg| format!("panicked: {msg}"),
+///     String => |msg| format!("panicked: {msg}"),
+///     _ => String::from("panicked")
+///  });
+///
+///  assert_eq!(join(|| 1), "got 1");
+///  assert_eq!(join(|| panic!("boom")), "panicked: boom");
+///  assert_eq!(join(|| panic!("worker {} crashed", 3)), "panicked: worker 3 crashed");
+///  assert_eq!(join(|| std::panic::panic_any(42)), "panicked");
+///
+///  let crashed = match_if_err!(std::thread::spawn(|| panic!("boom")).join(), { _ => true });
+///  assert!(crashed);
+/// ```
+#[macro_export]
+macro_rules! match_if_err {
+    (@match $any:tt $ty:tt [($($ok:tt)+) $ok_arm:expr] [$($arms:tt)*] $($default:expr)?) => (
+        match $any {
+            Ok($($ok)+) => $ok_arm,
+            Err(ref e) => $crate::match_err!(@arms top (*e) $ty [] $($arms)*),
+        }
+    );
+
+    (@match $any:tt $ty:tt [] [$($arms:tt)*] $default:expr) => (
+        if let Some(e) = $crate::__private::MaybeErr::maybe_err(&$any) {
+            $crate::match_err!(@arms top (*e) $ty [] $($arms)*)
+        } else {
+            $default
+        }
+    );
+
+    (@match $any:tt $ty:tt [] [$($arms:tt)*]) => (
+        compile_error!("binding the error in the fallback arm of match_if_err! requires an `Ok(..)` arm")
+    );
+
+    (@arms $any:tt $ty:tt [$($ok:tt)*] [$($arms:tt)*] Ok( $($value:tt)+ ) => $ok_arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_if_err!(@arms $any $ty [($($value)+) $ok_arm] [$($arms)*] $($($rest)*)?)
+    );
+
+    (@arms $any:tt $ty:tt [$($ok:tt)*] [$($arms:tt)*] _ => $default:expr $(,)?) => (
+        $crate::match_if_err!(@match $any $ty [$($ok)*] [$($arms)* _ => $default] $default)
+    );
+
+    (@arms $any:tt $ty:tt [$($ok:tt)*] [$($arms:tt)*] $bind:ident @ _ => $default:expr $(,)?) => (
+        $crate::match_if_err!(@match $any $ty [$($ok)*] [$($arms)* $bind @ _ => $default])
+    );
+
+    (@arms $any:tt $ty:tt [$($ok:tt)*] [$($arms:tt)*] $(,)?) => (
+        $crate::match_if_err!(@match $any $ty [$($ok)*] [$($arms)*] {})
+    );
+
+    (@arms $any:tt $ty:tt [$($ok:tt)*] [$($arms:tt)*] $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_if_err!(@arms $any $ty [$($ok)*] [
+            $($arms)*
+            $( #[$attr] )* $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => $arm,
+        ] $($($rest)*)?)
+    );
+
+    (@panic $any:tt [($($ok:tt)+) $ok_arm:expr] [$($arms:tt)*] $($default:expr)?) => (
+        match $any {
+            Ok($($ok)+) => $ok_arm,
+            Err(ref payload) => $crate::match_panic!(payload, { $($arms)* }),
+        }
+    );
+
+    (@panic $any:tt [] [$($arms:tt)*] $default:expr) => (
+        match $any {
+            Ok(_) => $default,
+            Err(ref payload) => $crate::match_panic!(payload, { $($arms)* }),
+        }
+    );
+
+    (@panic $any:tt [] [$($arms:tt)*]) => (
+        compile_error!("binding the payload in the fallback arm of match_if_err! requires an `Ok(..)` arm")
+    );
+
+    (@panic_arms $any:tt [$($ok:tt)*] [$($arms:tt)*] Ok( $($value:tt)+ ) => $ok_arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_if_err!(@panic_arms $any [($($value)+) $ok_arm] [$($arms)*] $($($rest)*)?)
+    );
+
+    (@panic_arms $any:tt [$($ok:tt)*] [$($arms:tt)*] _ => $default:expr $(,)?) => (
+        $crate::match_if_err!(@panic $any [$($ok)*] [$($arms)* _ => $default] $default)
+    );
+
+    (@panic_arms $any:tt [$($ok:tt)*] [$($arms:tt)*] $bind:ident @ _ => $default:expr $(,)?) => (
+        $crate::match_if_err!(@panic $any [$($ok)*] [$($arms)* $bind @ _ => $default])
+    );
+
+    (@panic_arms $any:tt [$($ok:tt)*] [$($arms:tt)*] $(,)?) => (
+        $crate::match_if_err!(@panic $any [$($ok)*] [$($arms)*] {})
+    );
+
+    (@panic_arms $any:tt [$($ok:tt)*] [$($arms:tt)*] $ty:ty => |$bind:pat_param| $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_if_err!(@panic_arms $any [$($ok)*] [$($arms)* $ty => |$bind| $arm,] $($($rest)*)?)
+    );
+
+    (@panic_arms $any:tt [$($ok:tt)*] [$($arms:tt)*] $ty:ty => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_if_err!(@panic_arms $any [$($ok)*] [$($arms)* $ty => $arm,] $($($rest)*)?)
+    );
+
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::match_if_err!(@arms $any [$($ty)::+ $(::<$($gen),+>)?] [] [] $($arms)*)
+    );
+
+    ( $any:expr, { $($arms:tt)* } ) => (
+        $crate::match_if_err!(@panic_arms $any [] [] $($arms)*)
+    );
+}
+
+/// Runs the arms of [`match_err!`] on the error of a result and returns the result unchanged, so
+/// it can sit in a call chain. Meant for logging at a level picked per variant. The fallback arm
+/// can bind the error with `e @ _`, `Ok` results don't run any arm
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use log::{debug, error, warn};
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("internal: {0}")]
+///     Internal(String),
+///  }
+///
+///  fn load(id: u32) -> anyhow::Result<u32> {
+///     match id {
+///         0 => Err(anyhow!(Error::NotFound)),
+///         1 => Err(anyhow!(Error::Internal(String::from("disk")))),
+///         _ => Ok(id),
+///     }
+///  }
+///
+///  let mut logged = Vec::new();
+///  for id in 0..3 {
+///     let res = log_if_err!(load(id), Error, {
+///         NotFound => { debug!("user {id} not found"); logged.push("debug") },
+///         Internal(e) => { error!("loading user {id}: {e}"); logged.push("error") },
+///         e @ _ => { warn!("{e:?}"); logged.push("warn") }
+///     });
+///     assert_eq!(res.is_ok(), id == 2);
+///  }
+///  assert_eq!(logged, ["debug", "error"]);
+///
+///  let res = log_if_err!(Err::<(), _>(anyhow!("unknown")), Error, { NotFound => debug!("not found") });
+///  assert!(res.is_err());
+/// ```
+#[macro_export]
+macro_rules! log_if_err {
+    ( $res:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        match $res {
+            result => {
+                if let Err(err) = &result {
+                    $crate::match_err!(@arms top err [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*);
+                }
+                result
+            }
+        }
+    );
+}
+
+/// Checks whether the error is of an enum-like error type and matches the given variant, like `std::matches!`
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("timeout after {secs}s")]
+///     Timeout { secs: u64 }
+///  }
+///
+///  let err = anyhow!(Error::Custom(String::from("db: connection lost")));
+///
+///  assert!(matches_err!(err, Error, Custom(_)));
+///  assert!(matches_err!(err, Error, Custom(msg) if msg.starts_with("db:")));
+///  assert!(matches_err!(err, Error, NotFound | Custom(_)));
+///  assert!(!matches_err!(err, Error, NotFound));
+///  assert!(!matches_err!(err, Error, Timeout { .. }));
+///  assert!(!matches_err!(anyhow!("unknown"), Error, Custom(_)));
+///
+///  let errors = vec![anyhow!(Error::NotFound), anyhow!(Error::Timeout { secs: 5 })];
+///  let retryable = errors.iter().filter(|e| matches_err!(e, Error, Timeout { .. })).count();
+///  assert_eq!(retryable, 1);
+/// ```
+#[macro_export]
+macro_rules! matches_err {
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? $(,)? ) => (
+        $crate::match_err!($any, $($ty)::+ $(::<$($gen),+>)?, {
+            $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => true,
+            _ => false
+        })
+    );
+}
+
+/// Runs the block if the error matches a single pattern of an enum-like error type, with an
+/// optional guard and `else` block. The type is taken from the path of the pattern, the bindings
+/// are references into the error
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let err = anyhow!(Error::NotFound);
+///  let mut handled = false;
+///  if_err_is!(err, Error::NotFound, { handled = true });
+///  assert!(handled);
+///
+///  let err = anyhow!(Error::Custom(String::from("db: connection lost")));
+///  let status = if_err_is!(err, Error::Custom(msg) if msg.starts_with("db:"), { 503 } else { 500 });
+///  assert_eq!(status, 503);
+///
+///  let status = if_err_is!(anyhow!("unknown"), Error::NotFound | Error::Custom(_), { 400 } else { 500 });
+///  assert_eq!(status, 500);
+/// ```
+#[macro_export]
+macro_rules! if_err_is {
+    ( $any:expr, $pat:pat $( if $guard:expr )?, $then:block $( else $otherwise:block )? $(,)? ) => (
+        match &$any {
+            any => match $crate::__match_err_probe!(match_err_downcast any) {
+                actual => {
+                    $crate::__match_err_hook!(@opt actual);
+                    match actual {
+                        Some($pat) $( if $guard )? => $then,
+                        _ => { $( $otherwise )? }
+                    }
+                }
+            }
+        }
+    );
+}
+
+/// Returns early with an `anyhow::Error` built from the message if the error of the result matches
+/// a single pattern of an enum-like error type, and evaluates to the original result otherwise.
+/// The type is taken from the path of the pattern, the bindings are references into the error that
+/// can be used in the message. Requires the `anyhow` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("unrecoverable")]
+///     Unrecoverable,
+///     #[error("corrupted block {0}")]
+///     Corrupted(u64),
+///     #[error("busy")]
+///     Busy,
+///  }
+///
+///  fn load(res: anyhow::Result<u32>, ctx: &str) -> anyhow::Result<u32> {
+///     let res = bail_if_err_is!(res, Error::Unrecoverable, "fatal: {}", ctx);
+///     let res = bail_if_err_is!(res, Error::Corrupted(block) if *block > 0, "corrupted block {block}");
+///     Ok(res.unwrap_or(0))
+///  }
+///
+///  assert_eq!(load(Ok(7), "loading user").unwrap(), 7);
+///  assert_eq!(load(Err(anyhow!(Error::Busy)), "loading user").unwrap(), 0);
+///  assert_eq!(load(Err(anyhow!(Error::Corrupted(0))), "loading user").unwrap(), 0);
+///  assert_eq!(load(Err(anyhow!(Error::Unrecoverable)), "loading user").unwrap_err().to_string(), "fatal: loading user");
+///  assert_eq!(load(Err(anyhow!(Error::Corrupted(3))), "loading user").unwrap_err().to_string(), "corrupted block 3");
+/// ```
+#[cfg(feature = "anyhow")]
+#[macro_export]
+macro_rules! bail_if_err_is {
+    ( $res:expr, $pat:pat $( if $guard:expr )?, $($msg:tt)+ ) => (
+        match $res {
+            res => {
+                if let ::core::result::Result::Err(err) = &res {
+                    let actual = $crate::__match_err_probe!(match_err_downcast err);
+                    $crate::__match_err_hook!(@opt actual);
+                    match actual {
+                        Some($pat) $( if $guard )? => {
+                            return ::core::result::Result::Err(::core::convert::From::from(
+                                $crate::__private::anyhow::anyhow!($($msg)+),
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
+                res
+            }
+        }
+    );
+}
+
+/// Evaluates to `Ok(())` if the result is `Ok` or its error matches a single pattern of an
+/// enum-like error type, and to the error otherwise, for writes that are allowed to have already
+/// happened. The type is taken from the path of the pattern
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("{0} already exists")]
+///     AlreadyExists(String),
+///     #[error("permission denied")]
+///     PermissionDenied,
+///  }
+///
+///  fn create(res: anyhow::Result<u32>) -> anyhow::Result<()> {
+///     ensure_ok_or_variant!(res, Error::AlreadyExists(_))?;
+///     Ok(())
+///  }
+///
+///  assert!(create(Ok(1)).is_ok());
+///  assert!(create(Err(anyhow!(Error::AlreadyExists(String::from("bucket"))))).is_ok());
+///  assert_eq!(create(Err(anyhow!(Error::PermissionDenied))).unwrap_err().to_string(), "permission denied");
+///
+///  let res: anyhow::Result<()> = Err(anyhow!(Error::AlreadyExists(String::from("tmp"))));
+///  assert!(ensure_ok_or_variant!(res, Error::AlreadyExists(name) if name.starts_with("tmp")).is_ok());
+/// ```
+#[macro_export]
+macro_rules! ensure_ok_or_variant {
+    ( $res:expr, $pat:pat $( if $guard:expr )? $(,)? ) => (
+        match $res {
+            ::core::result::Result::Ok(_) => ::core::result::Result::Ok(()),
+            ::core::result::Result::Err(err) => {
+                let actual = $crate::__match_err_probe!(match_err_downcast &err);
+                $crate::__match_err_hook!(@opt actual);
+                let matched = match actual {
+                    Some($pat) $( if $guard )? => true,
+                    _ => false,
+                };
+                if matched {
+                    ::core::result::Result::Ok(())
+                } else {
+                    ::core::result::Result::Err(err)
+                }
+            }
+        }
+    );
+}
+
+/// Evaluates to the `Ok` value of the result, or returns the value of the first matching arm from
+/// the enclosing function if the error matches. The arms are the same as in [`match_err!`] without
+/// the fallback, errors that don't match any of them are returned converted with `From` like `?` does
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("unauthorized: {0}")]
+///     Unauthorized(String),
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum ApiError {
+///     #[error("forbidden for {0}")]
+///     Forbidden(String),
+///     #[error(transparent)]
+///     Other(#[from] anyhow::Error),
+///  }
+///
+///  fn find_user(res: anyhow::Result<u32>) -> Result<Option<u32>, ApiError> {
+///     let id = return_if_err_is!(res, Error, {
+///         NotFound => Ok(None),
+///         Unauthorized(user) => Err(ApiError::Forbidden(user.clone())),
+///     });
+///     Ok(Some(id))
+///  }
+///
+///  assert_eq!(find_user(Ok(42)).unwrap(), Some(42));
+///  assert_eq!(find_user(Err(anyhow!(Error::NotFound))).unwrap(), None);
+///  assert!(matches!(
+///     find_user(Err(anyhow!(Error::Unauthorized(String::from("bob"))))),
+///     Err(ApiError::Forbidden(user)) if user == "bob"
+///  ));
+///  assert!(matches!(find_user(Err(anyhow!("connection reset"))), Err(ApiError::Other(_))));
+/// ```
+#[macro_export]
+macro_rules! return_if_err_is {
+    ( $res:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, {
+        $( $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $arm:expr ),+ $(,)?
+    } $(,)? ) => (
+        match $res {
+            ::core::result::Result::Ok(value) => value,
+            ::core::result::Result::Err(err) => return $crate::match_err!(err, $($ty)::+ $(::<$($gen),+>)?, {
+                $( $( #[$attr] )* $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => $arm, )+
+                _ => ::core::result::Result::Err(::core::convert::From::from(err))
+            }),
+        }
+    );
+}
+
+/// Works like `?`, but evaluates to the value of the first matching arm instead of returning if
+/// the error matches. The arms are the same as in [`match_err!`] without the fallback, errors that
+/// don't match any of them are returned converted with `From`
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("timeout after {0}s")]
+///     Timeout(u64),
+///  }
+///
+///  fn limit(res: anyhow::Result<u32>) -> anyhow::Result<u32> {
+///     let limit = try_except!(res, Error, {
+///         NotFound => 10,
+///         Timeout(secs) if *secs < 5 => 1,
+///     });
+///     Ok(limit * 2)
+///  }
+///
+///  assert_eq!(limit(Ok(3)).unwrap(), 6);
+///  assert_eq!(limit(Err(anyhow!(Error::NotFound))).unwrap(), 20);
+///  assert_eq!(limit(Err(anyhow!(Error::Timeout(1)))).unwrap(), 2);
+///  assert_eq!(limit(Err(anyhow!(Error::Timeout(30)))).unwrap_err().to_string(), "timeout after 30s");
+///  assert_eq!(limit(Err(anyhow!("connection reset"))).unwrap_err().to_string(), "connection reset");
+/// ```
+#[macro_export]
+macro_rules! try_except {
+    ( $res:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, {
+        $( $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $arm:expr ),+ $(,)?
+    } $(,)? ) => (
+        match $res {
+            ::core::result::Result::Ok(value) => value,
+            ::core::result::Result::Err(err) => $crate::match_err!(err, $($ty)::+ $(::<$($gen),+>)?, {
+                $( $( #[$attr] )* $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => $arm, )+
+                _ => return ::core::result::Result::Err(::core::convert::From::from(err))
+            }),
+        }
+    );
+}
+
+/// Evaluates to the `Ok` value of the result, or matches its error like [`match_err!`] and
+/// evaluates to the value of the arm, so the arms need the `_` fallback
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("rate limited for {0}s")]
+///     RateLimited(u64),
+///  }
+///
+///  let cached = |secs: u64| vec![secs as u32];
+///  let load = |res: anyhow::Result<Vec<u32>>| unwrap_or_match_err!(res, Error, {
+///     NotFound => Vec::default(),
+///     RateLimited(secs) => cached(*secs),
+///     e @ _ => panic!("unexpected: {e}")
+///  });
+///
+///  assert_eq!(load(Ok(vec![1, 2])), [1, 2]);
+///  assert_eq!(load(Err(anyhow!(Error::NotFound))), []);
+///  assert_eq!(load(Err(anyhow!(Error::RateLimited(30)))), [30]);
+/// ```
+#[macro_export]
+macro_rules! unwrap_or_match_err {
+    ( $res:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } $(,)? ) => (
+        match $res {
+            ::core::result::Result::Ok(value) => value,
+            ::core::result::Result::Err(err) => $crate::match_err!(err, $($ty)::+ $(::<$($gen),+>)?, { $($arms)* }),
+        }
+    );
+}
+
+/// Converts the error of the result into an `anyhow::Error` and attaches the value of the first
+/// matching arm as its context, errors that don't match any of the arms are passed through without
+/// context. The arms are the same as in [`match_err!`] without the fallback. Requires the `anyhow` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("timeout")]
+///     Timeout,
+///     #[error("connection refused by {0}")]
+///     ConnRefused(String),
+///     #[error("disk full")]
+///     DiskFull,
+///  }
+///
+///  let query = |res: anyhow::Result<u32>| with_context_if!(res, Error, {
+///     Timeout => "database timed out",
+///     ConnRefused(host) if host.starts_with("db") => "database unreachable",
+///  });
+///
+///  assert_eq!(query(Ok(1)).unwrap(), 1);
+///  assert_eq!(format!("{:#}", query(Err(anyhow!(Error::Timeout))).unwrap_err()), "database timed out: timeout");
+///  assert_eq!(
+///     format!("{:#}", query(Err(anyhow!(Error::ConnRefused(String::from("db-1"))))).unwrap_err()),
+///     "database unreachable: connection refused by db-1",
+///  );
+///  assert_eq!(format!("{:#}", query(Err(anyhow!(Error::DiskFull))).unwrap_err()), "disk full");
+///
+///  let res: Result<(), Error> = Err(Error::Timeout);
+///  let err = with_context_if!(res, Error, { Timeout => String::from("loading user") }).unwrap_err();
+///  assert_eq!(format!("{err:#}"), "loading user: timeout");
+/// ```
+#[cfg(feature = "anyhow")]
+#[macro_export]
+macro_rules! with_context_if {
+    ( $res:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, {
+        $( $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $arm:expr ),+ $(,)?
+    } $(,)? ) => (
+        match $res {
+            ::core::result::Result::Ok(value) => ::core::result::Result::<_, $crate::__private::anyhow::Error>::Ok(value),
+            ::core::result::Result::Err(err) => {
+                let context = $crate::match_err!(err, $($ty)::+ $(::<$($gen),+>)?, {
+                    $( $( #[$attr] )* $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => Some($arm), )+
+                    _ => None
+                });
+                let err = $crate::__private::anyhow::Error::from(err);
+                match context {
+                    Some(context) => ::core::result::Result::Err(err.context(context)),
+                    None => ::core::result::Result::Err(err),
+                }
+            }
+        }
+    );
+}
+
+/// Downcasts the error and matches it against a single pattern like `let`-`else`, binding the
+/// payload in the current scope. The type is taken from the path of the pattern and the `else`
+/// block has to diverge. The bindings are references into the error, so it has to be a variable
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("timeout after {secs}s")]
+///     Timeout { secs: u64 }
+///  }
+///
+///  fn handle(err: anyhow::Error) -> String {
+///     let_err_as!(Error::Custom(msg) = err else { return String::from("fallback") });
+///     msg.to_uppercase()
+///  }
+///
+///  assert_eq!(handle(anyhow!(Error::Custom(String::from("busy")))), "BUSY");
+///  assert_eq!(handle(anyhow!(Error::NotFound)), "fallback");
+///  assert_eq!(handle(anyhow!("unknown")), "fallback");
+///
+///  let err = anyhow!(Error::Timeout { secs: 5 });
+///  let_err_as!(Error::Timeout { secs } = err else { panic!("not a timeout") });
+///  assert_eq!(*secs, 5);
+/// ```
+///
+/// The `else` block has to diverge
+/// ```compile_fail
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let err = anyhow!(Error::Custom(String::from("busy")));
+///  let_err_as!(Error::Custom(msg) = err else { println!("not custom") });
+/// ```
+#[macro_export]
+macro_rules! let_err_as {
+    (@split [$pat:pat] [$($any:tt)+] else $otherwise:block $(;)?) => (
+        let ::core::option::Option::Some($pat) = $crate::__match_err_probe!(match_err_downcast &($($any)+)) else $otherwise;
+    );
+
+    (@split [$pat:pat] [$($any:tt)*] $next:tt $($rest:tt)*) => (
+        $crate::let_err_as!(@split [$pat] [$($any)* $next] $($rest)*)
+    );
+
+    ( $pat:pat = $($rest:tt)+ ) => (
+        $crate::let_err_as!(@split [$pat] [] $($rest)+)
+    );
+}
+
+/// Extracts references to the payload of a variant as an `Option`, a tuple is returned for several fields.
+/// Fields that aren't needed can be skipped with `..`
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("code {0}: {1}")]
+///     Code(u16, String),
+///     #[error("timeout after {secs}s: {op}")]
+///     Timeout { secs: u64, op: String }
+///  }
+///
+///  let err = anyhow!(Error::Custom(String::from("internal")));
+///  let msg: Option<&String> = extract_err!(err, Error, Custom(msg));
+///  assert_eq!(msg.map(String::as_str), Some("internal"));
+///  assert_eq!(extract_err!(err, Error, Code(code, msg)), None);
+///
+///  let err = anyhow!(Error::Code(404, String::from("gone")));
+///  let Some((code, msg)) = extract_err!(err, Error, Code(code, msg)) else { panic!() };
+///  assert_eq!((*code, msg.as_str()), (404, "gone"));
+///  assert_eq!(extract_err!(err, Error, Code(code, ..)), Some(&404));
+///
+///  let err = anyhow!(Error::Timeout { secs: 5, op: String::from("read") });
+///  assert_eq!(extract_err!(err, Error, Timeout { secs }), Some(&5));
+///  assert_eq!(extract_err!(err, Error, Timeout { secs, op }), Some((&5, &String::from("read"))));
+/// ```
+#[macro_export]
+macro_rules! extract_err {
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, $variant:ident ( $bind:ident $(, ..)? $(,)? ) ) => (
+        $crate::match_err!($any, $($ty)::+ $(::<$($gen),+>)?, { $variant($bind, ..) => Some($bind), _ => None })
+    );
+
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, $variant:ident ( $($bind:ident),+ $(, ..)? $(,)? ) ) => (
+        $crate::match_err!($any, $($ty)::+ $(::<$($gen),+>)?, { $variant($($bind),+, ..) => Some(($($bind),+)), _ => None })
+    );
+
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, $variant:ident { $bind:ident $(, ..)? $(,)? } ) => (
+        $crate::match_err!($any, $($ty)::+ $(::<$($gen),+>)?, { $variant { $bind, .. } => Some($bind), _ => None })
+    );
+
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, $variant:ident { $($bind:ident),+ $(, ..)? $(,)? } ) => (
+        $crate::match_err!($any, $($ty)::+ $(::<$($gen),+>)?, { $variant { $($bind),+, .. } => Some(($($bind),+)), _ => None })
+    );
+}
+
+/// Renders a user-facing message for the error like [`match_err!`], with an arm per variant. The arms
+/// return a `String` or a `&str`. Without a `_` arm, errors that don't match are rendered with
+/// their `Display`. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let describe = |err: anyhow::Error| format_err_matched!(err, Error, {
+///     NotFound => "no such item",
+///     Custom(m) => format!("failed: {m}"),
+///     _ => format!("internal error")
+///  });
+///
+///  assert_eq!(describe(anyhow!(Error::NotFound)), "no such item");
+///  assert_eq!(describe(anyhow!(Error::Custom(String::from("disk full")))), "failed: disk full");
+///  assert_eq!(describe(anyhow!("connection reset")), "internal error");
+///
+///  let msg = format_err_matched!(anyhow!("connection reset"), Error, { NotFound => "no such item" });
+///  assert_eq!(msg, "connection reset");
+/// ```
+#[macro_export]
+macro_rules! format_err_matched {
+    (@arms $any:tt $ty:tt [$($arms:tt)*] _ => $default:expr $(,)?) => (
+        $crate::match_err!(@arms top $any $ty [] $($arms)* _ => {
+            let msg: $crate::__private::String = ::core::convert::Into::into($default);
+            msg
+        })
+    );
+
+    (@arms $any:tt $ty:tt [$($arms:tt)*] $bind:ident @ _ => $default:expr $(,)?) => (
+        $crate::match_err!(@arms top $any $ty [] $($arms)* $bind @ _ => {
+            let msg: $crate::__private::String = ::core::convert::Into::into($default);
+            msg
+        })
+    );
+
+    (@arms $any:tt $ty:tt [$($arms:tt)*] $(,)?) => (
+        $crate::match_err!(@arms top $any $ty [] $($arms)* err @ _ => $crate::__private::format!("{}", err))
+    );
+
+    (@arms $any:tt $ty:tt [$($arms:tt)*] $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::format_err_matched!(@arms $any $ty [
+            $($arms)*
+            $( #[$attr] )* $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => {
+                let msg: $crate::__private::String = ::core::convert::Into::into($arm);
+                msg
+            },
+        ] $($($rest)*)?)
+    );
+
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::format_err_matched!(@arms $any [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*)
+    );
+}
+
+/// Asserts the variable is an error of an enum-like error type and returns a reference to it.
+/// Panics with the actual value otherwise
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("timeout after {secs}s: {op}")]
+///     Timeout { secs: u64, op: String }
+///  }
+///
+///  let res: Result<(), _> = Err(anyhow!(Error::Timeout { secs: 5, op: String::from("read") }));
+///
+///  let Error::Timeout { secs, op } = unwrap_err_as!(res, Error);
+///  assert_eq!(*secs, 5);
+///  assert_eq!(op, "read");
+/// ```
+///
+/// ```should_panic
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let res: Result<(), _> = Err(anyhow!("connection reset"));
+///  unwrap_err_as!(res, Error);
+/// ```
+#[macro_export]
+macro_rules! unwrap_err_as {
+    ($var:expr, $ty:ty $(,)?) => (
+        match $var {
+            Ok(ref value) => $crate::__private::assert_failed("unwrap_err_as", format_args!("expected an error of type `{}`, got Ok({:?})", ::core::any::type_name::<$ty>(), value)),
+            Err(ref err) => match $crate::__match_err_probe!(match_err_downcast err, $ty) {
+                Some(e) => e,
+                None => $crate::__private::assert_failed("unwrap_err_as", format_args!("expected an error of type `{}`, got: {:?}", ::core::any::type_name::<$ty>(), err)),
+            },
+        }
+    );
+}
+
+/// Asserts the variable is an error and then asserts it against an enum-like error type by hiding the usage of downcast_ref method
+/// The error is required to implement PartialEq. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("tuple error")]
+///     Tuple((i32, i32)),
+///     #[error("timeout after {secs}s: {op}")]
+///     Timeout { secs: u64, op: String }
+///  }
+///
+///  let err: Result<(), _> = Err(anyhow!(Error::Custom(String::from("internal"))));
+///  assert_if_error!(err, Error, Custom(String::from("internal")));
+///
+///  let err: Result<(), _> = Err(anyhow!(Error::Tuple((1,1))));
+///  assert_if_error!(err, Error, Tuple((1,1)), "error message");
+///
+///  let err: Result<(), _> = Err(anyhow!(Error::Timeout { secs: 5, op: String::from("read") }));
+///  assert_if_error!(err, Error, Timeout { secs: 5, op: String::from("read") });
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! assert_if_error {
+    ($var:expr, $ty:ty, $variant:ident $( ( $inner:expr ) )? $( { $($fields:tt)* } )? $(, $($arg:tt)+)? ) => (
+        if let Err(ref err) = $var {
+            $crate::assert_error!(*err, $ty, $variant $( ( $inner ) )? $( { $($fields)* } )? $(, $($arg)+)? );
+        } else {
+            $crate::__private::assert_failed("assert_if_error", format_args!("not an error"))
+        }
+    )
+}
+
+/// Asserts the error against an enum-like error type by hiding the usage of downcast_ref method
+/// The error is required to implement PartialEq. Evaluates to a reference to the downcast error,
+/// so end it with `;` where `()` is expected. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("tuple error")]
+///     Tuple((i32, i32)),
+///     #[error("timeout after {secs}s: {op}")]
+///     Timeout { secs: u64, op: String }
+///  }
+///
+///  let err = anyhow!(Error::Custom(String::from("internal")));
+///  assert_error!(err, Error, Custom(String::from("internal")));
+///
+///  let err = anyhow!(Error::Tuple((1,1)));
+///  assert_error!(err, Error, Tuple((1,1)), "error message");
+///
+///  let err = anyhow!(Error::Timeout { secs: 5, op: String::from("read") });
+///  assert_error!(err, Error, Timeout { secs: 5, op: String::from("read") }, "error message");
+///
+///  let e = assert_error!(err, Error, Timeout { secs: 5, op: String::from("read") });
+///  assert_eq!(e.to_string(), "timeout after 5s: read");
+/// ```
+///
+/// Failures tell a wrong type from a wrong variant or payload, and print the expected value, the
+/// downcast error and the whole error with its chain
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::panic::catch_unwind;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let message = |f: fn()| *catch_unwind(f).unwrap_err().downcast::<String>().unwrap();
+///
+///  let msg = message(|| { assert_error!(anyhow!("unknown").context("loading"), Error, NotFound); });
+///  assert_eq!(msg, "assertion failed: wrong type\n expected: NotFound\n   actual: None\n     type: anyhow::Error\n    error: loading: unknown");
+///
+///  let msg = message(|| { assert_error!(anyhow!(Error::NotFound), Error, Custom(String::from("a")), "user {}", 1); });
+///  assert_eq!(msg, "assertion failed: user 1: wrong variant\n expected: Custom(\"a\")\n   actual: Some(NotFound)\n     type: anyhow::Error\n    error: not found");
+///
+///  let msg = message(|| { assert_error!(anyhow!(Error::Custom(String::from("b"))), Error, Custom(String::from("a"))); });
+///  assert!(msg.starts_with("assertion failed: wrong payload\n"));
+/// ```
+///
+/// The assertions fail through `#[track_caller]` functions, so a failure inside a `#[track_caller]`
+/// helper is reported at the line calling the helper
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::panic::{catch_unwind, set_hook};
+///  use std::sync::Mutex;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  #[track_caller]
+///  fn assert_not_found(err: anyhow::Error) {
+///     assert_error!(err, Error, NotFound);
+///  }
+///
+///  static LINE: Mutex<Option<u32>> = Mutex::new(None);
+///  set_hook(Box::new(|info| *LINE.lock().unwrap() = info.location().map(|location| location.line())));
+///
+///  let line = line!() + 1;
+///  let _ = catch_unwind(|| assert_not_found(anyhow!("unknown")));
+///  assert_eq!(*LINE.lock().unwrap(), Some(line));
+/// ```
+///
+/// Setting the `MATCH_ERR_JSON` environment variable makes failed assertions also write a JSON
+/// object on a single line, with the location of the assertion. `assert_error!` adds the expected
+/// error, the checked type and the chain, the other assertions add their message. Set it to `1` for
+/// stderr or to the path of a file to append to. Requires the `std` feature
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::panic::catch_unwind;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let path = std::env::temp_dir().join(format!("match_err_{}.jsonl", std::process::id()));
+///  std::env::set_var("MATCH_ERR_JSON", &path);
+///  let _ = catch_unwind(|| { assert_error!(anyhow!("unknown").context("loading"), Error, NotFound, "user {}", 1); });
+///
+///  let report = std::fs::read_to_string(&path).unwrap();
+///  std::fs::remove_file(&path).unwrap();
+///  assert!(report.starts_with(r#"{"assertion":"assert_error","kind":"wrong type","message":"user 1","expected":"NotFound","actual":null,"type":"anyhow::Error","error":"loading: unknown","chain":["loading","unknown"],"file":"#));
+///  assert!(report.ends_with("}\n"));
+/// ```
+///
+/// With `with` and a closure after the expected error, the closure compares the payloads instead
+/// of PartialEq, like case-insensitive strings or floats with a tolerance. It gets the actual and
+/// the expected payload of a tuple variant, or the whole actual and expected errors of a struct
+/// variant, so the error doesn't have to implement PartialEq
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::panic::catch_unwind;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("latency {ms}ms at {at}")]
+///     Slow { ms: f64, at: u64 },
+///  }
+///
+///  let err = anyhow!(Error::Custom(String::from("Disk Full")));
+///  assert_error!(err, Error, Custom(String::from("disk full")), with |a, b| a.eq_ignore_ascii_case(b));
+///
+///  let err = anyhow!(Error::Slow { ms: 10.02, at: 1700000000 });
+///  assert_error!(err, Error, Slow { ms: 10.0, at: 0 }, with |a, b| match (a, b) {
+///     (Error::Slow { ms: a, .. }, Error::Slow { ms: b, .. }) => (a - b).abs() < 0.1,
+///     _ => false,
+///  }, "timestamps are ignored");
+///
+///  let res = catch_unwind(|| assert_error!(err, Error, Custom(String::from("x")), with |a, b| a == b));
+///  let msg = *res.unwrap_err().downcast::<String>().unwrap();
+///  assert!(msg.starts_with("assertion failed: wrong variant\n expected: Custom(\"x\")"));
+/// ```
+///
+/// With the `pretty` feature a wrong payload also renders a diff of the pretty `Debug` output of
+/// the expected and actual errors, colored unless `NO_COLOR` is set
+/// ```
+/// # #[cfg(feature = "pretty")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::panic::catch_unwind;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("invalid user")]
+///     Invalid { id: u64, name: String, email: String },
+///  }
+///
+///  std::env::set_var("NO_COLOR", "1");
+///  let err = anyhow!(Error::Invalid { id: 1, name: String::from("ann"), email: String::from("ann@b.c") });
+///  let msg = *catch_unwind(|| {
+///     assert_error!(err, Error, Invalid { id: 1, name: String::from("ann"), email: String::from("ann@a.c") })
+///  }).unwrap_err().downcast::<String>().unwrap();
+///
+///  let diff = msg.split_once("diff: - expected / + actual\n").unwrap().1;
+///  assert_eq!(diff, "  Invalid {\n      id: 1,\n      name: \"ann\",\n-     email: \"ann@a.c\",\n+     email: \"ann@b.c\",\n  }");
+/// # }
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! assert_error {
+    (@fail $mismatch:ident) => (
+        $crate::__private::assert_error_failed(&$mismatch, None)
+    );
+
+    (@fail $mismatch:ident, $($arg:tt)+) => (
+        $crate::__private::assert_error_failed(&$mismatch, Some(format_args!($($arg)+)))
+    );
+
+    ($var:expr, $ty:ty, $variant:ident ( $inner:expr ), with $cmp:expr $(, $($arg:tt)+)? ) => (
+        match $crate::check_error!(@ref $var, $ty, $variant($inner), with $cmp) {
+            Ok(e) => e,
+            Err(mismatch) => $crate::assert_error!(@fail mismatch $(, $($arg)+)?),
+        }
+    );
+
+    ($var:expr, $ty:ty, $variant:ident { $($fields:tt)* }, with $cmp:expr $(, $($arg:tt)+)? ) => (
+        match $crate::check_error!(@ref $var, $ty, $variant { $($fields)* }, with $cmp) {
+            Ok(e) => e,
+            Err(mismatch) => $crate::assert_error!(@fail mismatch $(, $($arg)+)?),
+        }
+    );
+
+    ($var:expr, $ty:ty, $variant:ident $( ( $inner:expr ) )? $(, $($arg:tt)+)? ) => (
+        match $crate::check_error!(@ref $var, $ty, $variant $( ( $inner ) )?) {
+            Ok(e) => e,
+            Err(mismatch) => $crate::assert_error!(@fail mismatch $(, $($arg)+)?),
+        }
+    );
+
+    ($var:expr, $ty:ty, $variant:ident { $($fields:tt)* } $(, $($arg:tt)+)? ) => (
+        match $crate::check_error!(@ref $var, $ty, $variant { $($fields)* }) {
+            Ok(e) => e,
+            Err(mismatch) => $crate::assert_error!(@fail mismatch $(, $($arg)+)?),
+        }
+    )
+}
+
+/// Checks the error against an enum-like error type like [`assert_error!`], but returns the
+/// difference as an [`ErrorMismatch`] instead of panicking. Useful for custom test harnesses and fuzzing.
+/// The error is required to implement PartialEq. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  #[error("upstream failed")]
+///  struct Upstream(#[source] Error);
+///
+///  assert_eq!(check_error!(anyhow!(Error::NotFound), Error, NotFound), Ok(()));
+///
+///  let mismatch = check_error!(anyhow!(Error::Custom(String::from("a"))), Error, NotFound).unwrap_err();
+///  assert_eq!(mismatch.kind(), Mismatch::Variant);
+///  assert_eq!(mismatch.expected(), "NotFound");
+///  assert_eq!(mismatch.actual(), Some("Custom(\"a\")"));
+///
+///  let err = anyhow!(Upstream(Error::NotFound));
+///  let mismatch = check_error!(err, Error, Custom(String::from("a"))).unwrap_err();
+///  assert_eq!(mismatch.kind(), Mismatch::Type);
+///  assert_eq!(mismatch.actual(), None);
+///  assert_eq!(mismatch.type_name(), "anyhow::Error");
+///  assert_eq!(mismatch.chain(), ["upstream failed", "not found"]);
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! check_error {
+    (@check $var:expr, $ty:ty, $expected:expr) => (
+        match &$var {
+            err => {
+                let expected: $ty = $expected;
+                let actual = $crate::__match_err_probe!(match_err_downcast err, $ty);
+                $crate::__match_err_hook!(@opt actual);
+                let mismatch = match actual {
+                    Some(e) => $crate::Mismatch::of(e, &expected),
+                    None => Some($crate::Mismatch::Type),
+                };
+                match (actual, mismatch) {
+                    (Some(e), None) => Ok(e),
+                    (_, kind) => Err($crate::ErrorMismatch::new(
+                        kind.unwrap_or($crate::Mismatch::Type),
+                        &expected,
+                        actual.map(|e| e as &dyn ::core::fmt::Debug),
+                        err,
+                        $crate::__match_err_probe!(match_err_dyn err),
+                    )),
+                }
+            }
+        }
+    );
+
+    (@with $var:expr, $ty:ty, $expected:expr, |$actual:ident, $wanted:ident| $actual_pat:pat, $wanted_pat:pat, $cmp:expr) => (
+        match &$var {
+            err => {
+                let expected: $ty = $expected;
+                let actual = $crate::__match_err_probe!(match_err_downcast err, $ty);
+                $crate::__match_err_hook!(@opt actual);
+                #[allow(unreachable_patterns)]
+                let mismatch = match (actual, &expected) {
+                    (Some($actual_pat), $wanted_pat) => if $crate::__private::compare_with($actual, $wanted, $cmp) {
+                        None
+                    } else {
+                        Some($crate::Mismatch::Payload)
+                    },
+                    (Some(_), _) => Some($crate::Mismatch::Variant),
+                    (None, _) => Some($crate::Mismatch::Type),
+                };
+                match (actual, mismatch) {
+                    (Some(e), None) => Ok(e),
+                    (_, kind) => Err($crate::ErrorMismatch::new(
+                        kind.unwrap_or($crate::Mismatch::Type),
+                        &expected,
+                        actual.map(|e| e as &dyn ::core::fmt::Debug),
+                        err,
+                        $crate::__match_err_probe!(match_err_dyn err),
+                    )),
+                }
+            }
+        }
+    );
+
+    (@ref $var:expr, $ty:ty, $variant:ident ( $inner:expr ), with $cmp:expr $(,)?) => ({
+        type Expected = $ty;
+        $crate::check_error!(@with $var, $ty, Expected::$variant($inner), |a, b| Expected::$variant(a), Expected::$variant(b), $cmp)
+    });
+
+    (@ref $var:expr, $ty:ty, $variant:ident { $($fields:tt)* }, with $cmp:expr $(,)?) => ({
+        type Expected = $ty;
+        $crate::check_error!(@with $var, $ty, Expected::$variant { $($fields)* }, |a, b| a @ Expected::$variant { .. }, b @ Expected::$variant { .. }, $cmp)
+    });
+
+    (@ref $var:expr, $ty:ty, $variant:ident $( ( $inner:expr ) )? $(,)?) => (
+        $crate::check_error!(@check $var, $ty, <$ty>::$variant $( ( $inner ) )?)
+    );
+
+    (@ref $var:expr, $ty:ty, $variant:ident { $($fields:tt)* } $(,)?) => (
+        // struct expressions can't use a qualified `<$ty>::` path, so go through an alias
+        $crate::check_error!(@check $var, $ty, { type Expected = $ty; Expected::$variant { $($fields)* } })
+    );
+
+    // the `@ref` rules return the downcast error for the assertions
+    ($($input:tt)+) => (
+        $crate::check_error!(@ref $($input)+).map(|_| ())
+    );
+}
+
+/// Checks the error like [`check_error!`] and records a mismatch in an [`ErrorAssertions`] session
+/// instead of panicking. Returns `true` if the check passed. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("timeout after {secs}s")]
+///     Timeout { secs: u64 },
+///  }
+///
+///  let mut session = ErrorAssertions::new();
+///  for secs in [1, 2, 3] {
+///     let err = anyhow!(Error::Timeout { secs });
+///     soft_assert_error!(session, err, Error, Timeout { secs });
+///  }
+///  assert!(!soft_assert_error!(session, anyhow!("unknown"), Error, Timeout { secs: 1 }));
+///  assert_eq!(session.failures().count(), 1);
+///  assert!(std::panic::catch_unwind(move || session.verify()).is_err());
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! soft_assert_error {
+    ($session:expr, $var:expr, $ty:ty, $($expected:tt)+) => (
+        $session.check($crate::check_error!($var, $ty, $($expected)+))
+    );
+}
+
+/// Runs a function over a table of inputs and asserts each one fails with the expected variant
+/// like [`assert_error!`]. The rows are `(input, Variant)`, `(input, Variant(payload))` or
+/// `(input, Variant { fields })`, and a failure reports the index of the row and the `Debug` of its
+/// input. The function returns a `Result` with any [`Downcastable`] error, and the error type is
+/// required to implement PartialEq. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("empty input")]
+///     Empty,
+///     #[error("invalid digit: {0}")]
+///     InvalidDigit(char),
+///     #[error("{value} is out of range")]
+///     OutOfRange { value: u64 },
+///  }
+///
+///  fn parse(input: &str) -> anyhow::Result<u8> {
+///     if input.is_empty() {
+///         anyhow::bail!(Error::Empty);
+///     }
+///     if let Some(c) = input.chars().find(|c| !c.is_ascii_digit()) {
+///         anyhow::bail!(Error::InvalidDigit(c));
+///     }
+///     let value = input.parse::<u64>()?;
+///     Ok(u8::try_from(value).map_err(|_| Error::OutOfRange { value })?)
+///  }
+///
+///  assert_error_table!(parse, Error, [
+///     ("", Empty),
+///     ("1x", InvalidDigit('x')),
+///     ("-1", InvalidDigit('-')),
+///     ("256", OutOfRange { value: 256 }),
+///  ]);
+///
+///  let panic = std::panic::catch_unwind(|| assert_error_table!(parse, Error, [
+///     ("", Empty),
+///     ("42", Empty),
+///  ])).unwrap_err();
+///  assert_eq!(
+///     panic.downcast_ref::<String>().unwrap(),
+///     "assertion failed: row 1 (\"42\"): expected an error, got Ok(42)",
+///  );
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! assert_error_table {
+    ($f:expr, $ty:ty, [ $( ( $input:expr, $($expected:tt)+ ) ),* $(,)? ] $(,)?) => ({
+        let f = $f;
+        let mut row = 0usize;
+        $(
+            let input = $input;
+            let shown = $crate::__private::format!("{:?}", input);
+            match f(input) {
+                Ok(value) => $crate::__private::assert_failed(
+                    "assert_error_table",
+                    format_args!("assertion failed: row {} ({}): expected an error, got Ok({:?})", row, shown, value),
+                ),
+                Err(err) => if let Err(mismatch) = $crate::check_error!(err, $ty, $($expected)+) {
+                    $crate::__private::assert_error_failed(&mismatch, Some(format_args!("row {} ({})", row, shown)))
+                },
+            }
+            row += 1;
+        )*
+        let _ = row;
+    });
+}
+
+/// Expands a list of cases into one `#[test]` per case, calling the function with the arguments
+/// of the case and asserting it fails with the expected variant like [`assert_error!`]. The tests
+/// are named after the function and the variant, like `parse_invalid_digit`, with `_2`, `_3` and
+/// so on appended when a variant repeats, so each case shows up in the test output and can be run
+/// alone. The error type is required to implement PartialEq. Requires the `macros` and `alloc`
+/// features
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("empty input")]
+///     Empty,
+///     #[error("invalid digit: {0}")]
+///     InvalidDigit(char),
+///     #[error("{value} is out of range for base {base}")]
+///     OutOfRange { value: u64, base: u32 },
+///  }
+///
+///  fn parse(input: &str, base: u32) -> anyhow::Result<u8> {
+///     if input.is_empty() {
+///         anyhow::bail!(Error::Empty);
+///     }
+///     if let Some(c) = input.chars().find(|c| !c.is_digit(base)) {
+///         anyhow::bail!(Error::InvalidDigit(c));
+///     }
+///     let value = u64::from_str_radix(input, base)?;
+///     Ok(u8::try_from(value).map_err(|_| Error::OutOfRange { value, base })?)
+///  }
+///
+///  // expands to `parse_empty`, `parse_invalid_digit`, `parse_invalid_digit_2` and `parse_out_of_range`
+///  test_error_variants!(parse, Error, {
+///     ("", 10) => Empty,
+///     ("1x", 10) => InvalidDigit('x'),
+///     ("12", 2) => InvalidDigit('2'),
+///     ("100", 16) => OutOfRange { value: 256, base: 16 },
+///  });
+/// ```
+#[cfg(all(feature = "macros", feature = "alloc"))]
+#[macro_export]
+macro_rules! test_error_variants {
+    ($($input:tt)*) => {
+        $crate::__private::__test_error_variants! { $crate; $($input)* }
+    };
+}
+
+/// Verifies the error against a pattern of an enum-like error type with an optional guard like
+/// [`assert_err_matches!`], but returns the difference as an [`ErrorMismatch`] instead of
+/// panicking, so property-based tests and soft assertions can collect failures. Like
+/// [`check_error!`] it tells a wrong type from a wrong variant or a wrong payload, but the error
+/// isn't required to implement PartialEq. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("timeout after {secs}s: {op}")]
+///     Timeout { secs: u64, op: String },
+///  }
+///
+///  let err = anyhow!(Error::Timeout { secs: 5, op: String::from("read") });
+///  assert!(verify_error!(err, Error, Timeout { secs, .. } if *secs < 10).is_ok());
+///  assert!(verify_error!(err, Error, NotFound | Timeout { .. }).is_ok());
+///
+///  let mismatch = verify_error!(err, Error, Timeout { secs: 10, .. }).unwrap_err();
+///  assert_eq!(mismatch.kind(), Mismatch::Payload);
+///  assert_eq!(mismatch.expected(), "Timeout { secs: 10, .. }");
+///  assert_eq!(mismatch.actual(), Some("Timeout { secs: 5, op: \"read\" }"));
+///
+///  assert_eq!(verify_error!(err, Error, NotFound).unwrap_err().kind(), Mismatch::Variant);
+///
+///  let mismatch = verify_error!(anyhow!("unknown").context("loading"), Error, NotFound).unwrap_err();
+///  assert_eq!(mismatch.kind(), Mismatch::Type);
+///  assert_eq!(mismatch.type_name(), "anyhow::Error");
+///  assert_eq!(mismatch.chain(), ["loading", "unknown"]);
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! verify_error {
+    (@verify $var:expr, $ty:tt, $pattern:tt, $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? $(,)?) => (
+        match &$var {
+            err => {
+                let actual = $crate::__match_err_probe!(match_err_downcast err, $crate::__match_err_path!($ty));
+                $crate::__match_err_hook!(@opt actual);
+                let mismatch = match actual {
+                    Some($( $crate::__match_err_path!($ty $variant $( ( $($inner)+ ) )? $( { $($fields)* } )?) )|+) $( if $guard )? => None,
+                    #[allow(unreachable_patterns)]
+                    Some($( $crate::__match_err_path!($ty $variant { .. }) )|+) => Some($crate::Mismatch::Payload),
+                    #[allow(unreachable_patterns)]
+                    Some(_) => Some($crate::Mismatch::Variant),
+                    None => Some($crate::Mismatch::Type),
+                };
+                match mismatch {
+                    None => Ok(()),
+                    Some(kind) => Err($crate::ErrorMismatch::new(
+                        kind,
+                        &format_args!("{}", stringify! $pattern),
+                        actual.map(|e| e as &dyn ::core::fmt::Debug),
+                        err,
+                        $crate::__match_err_probe!(match_err_dyn err),
+                    )),
+                }
+            }
+        }
+    );
+
+    ( $var:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, $($pattern:tt)+ ) => (
+        $crate::verify_error!(@verify $var, [$($ty)::+ $(::<$($gen),+>)?], ($($pattern)+), $($pattern)+)
+    );
+}
+
+/// Builds a [`VariantMatcher`] from a pattern of an enum-like error type with an optional guard,
+/// written with the path of the type like `Error::Custom(_)`. Generic error types aren't supported
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let cases = [
+///     (anyhow!(Error::Custom("disk".into())), variant!(Error::Custom(_))),
+///     (anyhow!(Error::Custom("".into())), variant!(Error::Custom(msg) if msg.is_empty())),
+///  ];
+///  for (err, expected) in cases {
+///     expected.assert(&err);
+///  }
+/// ```
+#[macro_export]
+macro_rules! variant {
+    (@split $pattern:tt, [$($ty:tt)+] :: $segment:ident :: $($rest:tt)+) => (
+        $crate::variant!(@split $pattern, [$($ty)+ :: $segment] :: $($rest)+)
+    );
+
+    (@split $pattern:tt, [$($ty:tt)+] :: $variant:ident $($rest:tt)*) => (
+        $crate::VariantMatcher::<$($ty)+>::new(
+            stringify! $pattern,
+            |err: &$($ty)+| ::core::matches!(err, $($ty)+ :: $variant $($rest)*),
+            |err: &$($ty)+| ::core::matches!(err, $($ty)+ :: $variant { .. }),
+        )
+    );
+
+    (@start $pattern:tt, $first:ident $($rest:tt)+) => (
+        $crate::variant!(@split $pattern, [$first] $($rest)+)
+    );
+
+    ($($pattern:tt)+) => (
+        $crate::variant!(@start ($($pattern)+), $($pattern)+)
+    );
+}
+
+/// Asserts the error against a tuple variant of an enum-like error type carrying floats, like
+/// [`assert_error!`] but with the payload compared within an absolute epsilon. The payload can be
+/// an `f32` or `f64`, a pair or an array of them. The error isn't required to implement PartialEq.
+/// Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("ratio {0} is out of range")]
+///     Ratio(f64),
+///     #[error("point {0:?} is outside")]
+///     Outside((f32, f32)),
+///  }
+///
+///  let err = anyhow!(Error::Ratio(1.0 / 3.0));
+///  assert_error_approx!(err, Error, Ratio(0.333333), epsilon = 1e-6);
+///
+///  let err = anyhow!(Error::Outside((0.1 + 0.2, 1.0)));
+///  assert_error_approx!(err, Error, Outside((0.3, 1.0)), epsilon = 1e-6, "point {}", 1);
+///
+///  let res = std::panic::catch_unwind(|| { assert_error_approx!(anyhow!(Error::Ratio(0.5)), Error, Ratio(0.6), epsilon = 0.01); });
+///  let msg = *res.unwrap_err().downcast::<String>().unwrap();
+///  assert!(msg.starts_with("assertion failed: wrong payload\n expected: Ratio(0.6)\n   actual: Some(Ratio(0.5))"));
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! assert_error_approx {
+    ($var:expr, $ty:ty, $variant:ident ( $inner:expr ), epsilon = $epsilon:expr $(, $($arg:tt)+)? ) => (
+        $crate::assert_error!(
+            $var, $ty, $variant($inner),
+            with |a, b| $crate::__private::ApproxEq::approx_eq(a, b, $epsilon)
+            $(, $($arg)+)?
+        )
+    );
+}
+
+/// Asserts the error is one of the given variants of an enum-like error type, ignoring the payload.
+/// Unlike [`assert_error!`] the error isn't required to implement PartialEq, so it works for
+/// variants holding errors like `io::Error`. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("io: {0}")]
+///     Io(#[from] std::io::Error),
+///     #[error("invalid header {name}")]
+///     InvalidHeader { name: String, source: std::str::Utf8Error },
+///     #[error("closed")]
+///     Closed,
+///  }
+///
+///  let err = anyhow!(Error::Io(std::io::Error::other("broken pipe")));
+///  assert_error_variant!(err, Error, Io);
+///  assert_error_variant!(err, Error, Io | Closed, "reading {}", "the body");
+///
+///  let res = std::panic::catch_unwind(|| assert_error_variant!(anyhow!(Error::Closed), Error, InvalidHeader));
+///  let msg = res.unwrap_err().downcast::<String>().unwrap();
+///  assert!(msg.starts_with("assertion failed: wrong variant\n expected: InvalidHeader { .. }\n   actual: Some(Closed)"));
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! assert_error_variant {
+    ( $var:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, $($variant:ident)|+ $(, $($arg:tt)+)? ) => (
+        if let Err(mismatch) = $crate::verify_error!($var, $($ty)::+ $(::<$($gen),+>)?, $($variant { .. })|+) {
+            $crate::assert_error!(@fail mismatch $(, $($arg)+)?)
+        }
+    );
+}
+
+/// Asserts the error against a pattern of an enum-like error type with an optional guard.
+/// Unlike [`assert_error!`] the error isn't required to implement PartialEq. Evaluates to a
+/// reference to the downcast error, so checks of its fields can follow
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("io: {0}")]
+///     Io(#[from] std::io::Error),
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let err = anyhow!(Error::Custom(String::from("disk is full")));
+///  assert_err_matches!(err, Error::Custom(msg) if msg.contains("disk"));
+///
+///  let err = anyhow!(Error::Io(std::io::Error::other("broken pipe")));
+///  assert_err_matches!(err, Error::Io(_) | Error::Custom(_), "expected an io error");
+///
+///  let e = assert_err_matches!(err, Error::Io(_));
+///  assert_eq!(e.to_string(), "io: broken pipe");
+/// ```
+///
+/// ```should_panic
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let err = anyhow!(Error::Custom(String::from("permission denied")));
+///  assert_err_matches!(err, Error::Custom(msg) if msg.contains("disk"));
+/// ```
+#[macro_export]
+macro_rules! assert_err_matches {
+    ($var:expr, $pattern:pat $( if $guard:expr )? $(,)?) => (
+        match $var {
+            ref err => match $crate::__match_err_probe!(match_err_downcast err) {
+                actual => {
+                    $crate::__match_err_hook!(@opt actual);
+                    match actual {
+                        Some(e @ ($pattern)) $( if $guard )? => e,
+                        _ => $crate::__private::assert_failed(
+                            "assert_err_matches",
+                            format_args!("assertion failed: error doesn't match `{}`: {:?}", stringify!($pattern $( if $guard )?), err),
+                        ),
+                    }
+                }
+            }
+        }
+    );
+
+    ($var:expr, $pattern:pat $( if $guard:expr )?, $($arg:tt)+) => (
+        match $crate::__match_err_probe!(match_err_downcast &$var) {
+            actual => {
+                $crate::__match_err_hook!(@opt actual);
+                match actual {
+                    Some(e @ ($pattern)) $( if $guard )? => e,
+                    _ => $crate::__private::assert_failed("assert_err_matches", format_args!($($arg)+)),
+                }
+            }
+        }
+    );
+}
+
+/// Asserts the error doesn't match the given variant of an enum-like error type.
+/// Errors of other types or variants pass the assertion
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let err = anyhow!(Error::Custom(String::from("internal")));
+///  assert_not_error!(err, Error, NotFound);
+///  assert_not_error!(err, Error, Custom(msg) if msg.is_empty(), "empty message");
+///  assert_not_error!(anyhow!("unknown"), Error, NotFound | Custom(_));
+/// ```
+///
+/// ```should_panic
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  assert_not_error!(anyhow!(Error::NotFound), Error, NotFound);
+/// ```
+#[macro_export]
+macro_rules! assert_not_error {
+    ( $var:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? $(,)? ) => (
+        $crate::match_err!($var, $($ty)::+ $(::<$($gen),+>)?, {
+            $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => $crate::__private::assert_failed(
+                "assert_not_error",
+                format_args!(
+                    "assertion failed: error matches `{}`",
+                    stringify!($( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )?)
+                ),
+            ),
+            _ => {}
+        })
+    );
+
+    ( $var:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )?, $($arg:tt)+ ) => (
+        $crate::match_err!($var, $($ty)::+ $(::<$($gen),+>)?, {
+            $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => $crate::__private::assert_failed("assert_not_error", format_args!($($arg)+)),
+            _ => {}
+        })
+    );
+}
+
+/// Asserts the structure of the cause chain, checking the error and each of its sources in order.
+/// A layer is either a type, a variant pattern, or `_` for any layer. Unit variants are written
+/// with braces like `Error::NotFound {}`, since a bare path is taken as a type. The chain has to end
+/// after the last layer unless the list ends with `..`
+///
+/// Note that context added with `.context()` is a layer of its own
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::io;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum StoreError {
+///     #[error("read failed")]
+///     Read(#[source] io::Error),
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum ApiError {
+///     #[error("upstream failed")]
+///     Upstream(#[source] StoreError),
+///  }
+///
+///  let err = anyhow!(ApiError::Upstream(StoreError::Read(io::Error::other("disk"))));
+///  assert_error_chain!(err, [ApiError::Upstream(_), StoreError::Read(_), io::Error]);
+///  assert_error_chain!(err, [ApiError, StoreError, ..]);
+///
+///  let err = anyhow!(ApiError::Upstream(StoreError::NotFound)).context("loading user");
+///  assert_error_chain!(err, [_, ApiError::Upstream(StoreError::NotFound), StoreError::NotFound {}]);
+/// ```
+///
+/// A dropped layer fails the assertion
+/// ```should_panic
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::io;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum ApiError {
+///     #[error("upstream failed: {0}")]
+///     Upstream(io::Error),
+///  }
+///
+///  let err = anyhow!(ApiError::Upstream(io::Error::other("disk")));
+///  assert_error_chain!(err, [ApiError::Upstream(_), io::Error]);
+/// ```
+#[macro_export]
+macro_rules! assert_error_chain {
+    (@next $layer:ident $depth:ident) => (
+        $layer = $layer.and_then(|e| e.source());
+        $depth += 1;
+    );
+
+    (@layers $layer:ident $root:ident $depth:ident ;) => (
+        if $layer.is_some() {
+            $crate::__private::assert_chain_failed($depth, "end of the chain", $layer, $root)
+        }
+    );
+
+    (@layers $layer:ident $root:ident $depth:ident ; .. $(,)?) => (
+        let _ = ($layer, $depth);
+    );
+
+    (@layers $layer:ident $root:ident $depth:ident ; _ $(, $($rest:tt)*)?) => (
+        if $layer.is_none() {
+            $crate::__private::assert_chain_failed($depth, "_", $layer, $root)
+        }
+        $crate::assert_error_chain!(@next $layer $depth);
+        $crate::assert_error_chain!(@layers $layer $root $depth ; $($($rest)*)?);
+    );
+
+    (@layers $layer:ident $root:ident $depth:ident ; $($seg:ident)::+ ( $($inner:tt)* ) $(, $($rest:tt)*)?) => (
+        match $layer.and_then(|e| <dyn ::core::error::Error>::downcast_ref(e)) {
+            Some($($seg)::+ ( $($inner)* )) => {}
+            _ => $crate::__private::assert_chain_failed($depth, stringify!($($seg)::+ ( $($inner)* )), $layer, $root),
+        }
+        $crate::assert_error_chain!(@next $layer $depth);
+        $crate::assert_error_chain!(@layers $layer $root $depth ; $($($rest)*)?);
+    );
+
+    (@layers $layer:ident $root:ident $depth:ident ; $($seg:ident)::+ { $($fields:tt)* } $(, $($rest:tt)*)?) => (
+        match $layer.and_then(|e| <dyn ::core::error::Error>::downcast_ref(e)) {
+            Some($($seg)::+ { $($fields)* }) => {}
+            _ => $crate::__private::assert_chain_failed($depth, stringify!($($seg)::+ { $($fields)* }), $layer, $root),
+        }
+        $crate::assert_error_chain!(@next $layer $depth);
+        $crate::assert_error_chain!(@layers $layer $root $depth ; $($($rest)*)?);
+    );
+
+    (@layers $layer:ident $root:ident $depth:ident ; $($seg:ident)::+ $(, $($rest:tt)*)?) => (
+        if !$layer.is_some_and(|e| e.is::<$($seg)::+>()) {
+            $crate::__private::assert_chain_failed($depth, stringify!($($seg)::+), $layer, $root)
+        }
+        $crate::assert_error_chain!(@next $layer $depth);
+        $crate::assert_error_chain!(@layers $layer $root $depth ; $($($rest)*)?);
+    );
+
+    ($var:expr, [ $($layers:tt)* ] $(,)?) => (
+        match &$var {
+            err => {
+                let root = $crate::Downcastable::to_dyn_error(err);
+                let mut layer = root;
+                let mut depth = 0usize;
+                $crate::assert_error_chain!(@layers layer root depth ; $($layers)*);
+            }
+        }
+    );
+}
+
+/// Asserts the `Display` rendering of the error equals, contains or starts with the given string.
+/// A leading `#` renders it with `{:#}`, which includes the whole chain for anyhow errors.
+/// Useful for foreign errors that can't be downcast to a known enum. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  let err = anyhow!("permission denied (os error 13)").context("opening config");
+///
+///  assert_error_msg!(err, "opening config");
+///  assert_error_msg!(err, # "opening config: permission denied (os error 13)");
+///  assert_error_msg!(err, # contains "permission denied");
+///  assert_error_msg!(err, starts_with "opening", "unexpected context");
+/// ```
+///
+/// With the `regex` feature the message can be matched against a pattern, which helps with
+/// dynamic payloads like ids and durations
+/// ```
+/// # #[cfg(feature = "regex")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  let err = anyhow!("timeout after 250ms").context("request 42 failed");
+///
+///  assert_error_msg!(err, matches r"^request \d+ failed$");
+///  assert_error_msg!(err, # matches r"timeout after \d+ms");
+/// # }
+/// ```
+///
+/// ```should_panic
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  let err = anyhow!("permission denied").context("opening config");
+///  assert_error_msg!(err, contains "permission denied");
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! assert_error_msg {
+    (@render [] $err:expr) => ($crate::__private::format!("{}", $err));
+
+    (@render [#] $err:expr) => ($crate::__private::format!("{:#}", $err));
+
+    (@check $var:expr, [$($alt:tt)?] $op:literal $method:ident $expected:expr $(, $($arg:tt)+)?) => (
+        match &$var {
+            err => {
+                let msg = $crate::assert_error_msg!(@render [$($alt)?] err);
+                let expected: &str = $expected;
+                if !msg.$method(expected) {
+                    $crate::assert_error_msg!(@fail $op msg expected $(, $($arg)+)?)
+                }
+            }
+        }
+    );
+
+    (@fail $op:literal $msg:ident $expected:ident) => (
+        $crate::__private::assert_failed(
+            "assert_error_msg",
+            format_args!("assertion failed: error message doesn't {} `{:?}`\n  message: {:?}", $op, $expected, $msg),
+        )
+    );
+
+    (@fail $op:literal $msg:ident $expected:ident, $($arg:tt)+) => (
+        $crate::__private::assert_failed(
+            "assert_error_msg",
+            format_args!("assertion failed: error message doesn't {} `{:?}`: {}\n  message: {:?}", $op, $expected, format_args!($($arg)+), $msg),
+        )
+    );
+
+    (@regex $var:expr, [$($alt:tt)?] $pattern:expr $(, $($arg:tt)+)?) => (
+        match &$var {
+            err => {
+                let msg = $crate::assert_error_msg!(@render [$($alt)?] err);
+                let expected: &str = $pattern;
+                if !$crate::__regex_is_match!(&msg, expected) {
+                    $crate::assert_error_msg!(@fail "match" msg expected $(, $($arg)+)?)
+                }
+            }
+        }
+    );
+
+    ($var:expr, # matches $pattern:expr $(, $($arg:tt)+)?) => (
+        $crate::assert_error_msg!(@regex $var, [#] $pattern $(, $($arg)+)?)
+    );
+
+    ($var:expr, matches $pattern:expr $(, $($arg:tt)+)?) => (
+        $crate::assert_error_msg!(@regex $var, [] $pattern $(, $($arg)+)?)
+    );
+
+    ($var:expr, # contains $expected:expr $(, $($arg:tt)+)?) => (
+        $crate::assert_error_msg!(@check $var, [#] "contain" contains $expected $(, $($arg)+)?)
+    );
+
+    ($var:expr, # starts_with $expected:expr $(, $($arg:tt)+)?) => (
+        $crate::assert_error_msg!(@check $var, [#] "start with" starts_with $expected $(, $($arg)+)?)
+    );
+
+    ($var:expr, # $expected:expr $(, $($arg:tt)+)?) => (
+        $crate::assert_error_msg!(@check $var, [#] "equal" eq $expected $(, $($arg)+)?)
+    );
+
+    ($var:expr, contains $expected:expr $(, $($arg:tt)+)?) => (
+        $crate::assert_error_msg!(@check $var, [] "contain" contains $expected $(, $($arg)+)?)
+    );
+
+    ($var:expr, starts_with $expected:expr $(, $($arg:tt)+)?) => (
+        $crate::assert_error_msg!(@check $var, [] "start with" starts_with $expected $(, $($arg)+)?)
+    );
+
+    ($var:expr, $expected:expr $(, $($arg:tt)+)?) => (
+        $crate::assert_error_msg!(@check $var, [] "equal" eq $expected $(, $($arg)+)?)
+    );
+}
+
+/// Like [`assert_if_error!`], but only checked in builds with debug assertions, like `debug_assert!`
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let res: Result<(), _> = Err(anyhow!(Error::NotFound));
+///  debug_assert_if_error!(res, Error, NotFound);
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! debug_assert_if_error {
+    ($($arg:tt)*) => (
+        if cfg!(debug_assertions) {
+            $crate::assert_if_error!($($arg)*);
+        }
+    );
+}
+
+/// Like [`assert_error!`], but only checked in builds with debug assertions, like `debug_assert!`
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum Error {
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let err = anyhow!(Error::Custom(String::from("internal")));
+///  debug_assert_error!(err, Error, Custom(String::from("internal")), "unexpected error");
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! debug_assert_error {
+    ($($arg:tt)*) => (
+        if cfg!(debug_assertions) {
+            $crate::assert_error!($($arg)*);
+        }
+    );
+}
+
+/// Like [`assert_err_matches!`], but only checked in builds with debug assertions, like `debug_assert!`
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("custom: {0}")]
+///     Custom(String),
+///  }
+///
+///  let err = anyhow!(Error::Custom(String::from("disk is full")));
+///  debug_assert_err_matches!(err, Error::Custom(msg) if msg.contains("disk"));
+/// ```
+#[macro_export]
+macro_rules! debug_assert_err_matches {
+    ($($arg:tt)*) => (
+        if cfg!(debug_assertions) {
+            $crate::assert_err_matches!($($arg)*);
+        }
+    );
+}
+
+/// Like [`assert_not_error!`], but only checked in builds with debug assertions, like `debug_assert!`
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  debug_assert_not_error!(anyhow!("unknown"), Error, NotFound);
+/// ```
+#[macro_export]
+macro_rules! debug_assert_not_error {
+    ($($arg:tt)*) => (
+        if cfg!(debug_assertions) {
+            $crate::assert_not_error!($($arg)*);
+        }
+    );
+}
+
+#[doc(hidden)]
+#[cfg(feature = "regex")]
+#[macro_export]
+macro_rules! __regex_is_match {
+    ($text:expr, $pattern:expr) => (
+        $crate::__private::regex::Regex::new($pattern)
+            .unwrap_or_else(|e| panic!("invalid regex `{}`: {}", $pattern, e))
+            .is_match($text)
+    );
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "regex"))]
+#[macro_export]
+macro_rules! __regex_is_match {
+    ($text:expr, $pattern:expr) => (
+        compile_error!("matching error messages against a regex requires the `regex` feature of match_err")
+    );
+}
+
+/// Reports invalid input of `match_err!`, the `macros` feature replaces it with a procedural macro
+/// pointing at the mistake
+#[doc(hidden)]
+#[cfg(not(feature = "macros"))]
+#[macro_export]
+macro_rules! __match_err_diagnose {
+    ($krate:path; $($input:tt)*) => (
+        compile_error!("invalid input of match_err!, expected `error, Type, { Variant => value, _ => value }`")
+    );
+}
+
+/// Calls the [`hooks`] registered for the type of the matched error, expands to nothing without
+/// the `hooks` feature
+#[doc(hidden)]
+#[cfg(feature = "hooks")]
+#[macro_export]
+macro_rules! __match_err_hook {
+    (@opt $actual:expr) => (
+        if let ::core::option::Option::Some(e) = $actual {
+            $crate::hooks::run(e)
+        }
+    );
+
+    ($err:expr) => ( $crate::hooks::run($err) );
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "hooks"))]
+#[macro_export]
+macro_rules! __match_err_hook {
+    (@opt $actual:expr) => ( () );
+    ($err:expr) => ( () );
+}
+
+/// Downcasts or searches the error through [`Probe`](crate::__private::Probe), so concrete errors
+/// that don't implement [`Downcastable`] are accepted as well. The type can be left to inference
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __match_err_probe {
+    ($method:ident $any:expr) => ({
+        #[allow(unused_imports)]
+        use $crate::__private::{ViaDowncastable as _, ViaError as _};
+        (&$crate::__private::Probe($any)).$method()
+    });
+
+    ($method:ident $any:expr, $ty:ty) => ({
+        #[allow(unused_imports)]
+        use $crate::__private::{ViaDowncastable as _, ViaError as _};
+        (&$crate::__private::Probe($any)).$method::<$ty>()
+    });
+}
+
+/// Matches the key of an error, like the kind of an `io::Error` found in its chain, against arms of
+/// variants of the key's enum with an optional `_` or `name @ _` fallback. The key expression gets
+/// the error through the binding named before `=>`, and evaluates to `None` for errors without one
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __match_err_keyed {
+    (@match [$bound:ident => $key:expr] [$($path:tt)*] $any:tt [$($arms:tt)*] [$($bind:ident)?] $default:expr) => (
+        match &$any {
+            $bound => if let Some(key) = $key {
+                use $($path)* as __MatchErrKey;
+                match key {
+                    $($arms)*
+                    _ => { $( let $bind = $bound; )? $default }
+                }
+            } else {
+                $( let $bind = $bound; )?
+                $default
+            }
+        }
+    );
+
+    (@arms $keyed:tt $path:tt $any:tt [$($arms:tt)*] _ => $default:expr $(,)?) => (
+        $crate::__match_err_keyed!(@match $keyed $path $any [$($arms)*] [] $default)
+    );
+
+    (@arms $keyed:tt $path:tt $any:tt [$($arms:tt)*] $bind:ident @ _ => $default:expr $(,)?) => (
+        $crate::__match_err_keyed!(@match $keyed $path $any [$($arms)*] [$bind] $default)
+    );
+
+    (@arms $keyed:tt $path:tt $any:tt [$($arms:tt)*] $(,)?) => (
+        $crate::__match_err_keyed!(@match $keyed $path $any [$($arms)*] [] {})
+    );
+
+    (@arms $keyed:tt $path:tt $any:tt [$($arms:tt)*] $( $variant:ident $( ( $($inner:tt)+ ) )? )|+ $( if $guard:expr )? => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::__match_err_keyed!(@arms $keyed $path $any [
+            $($arms)*
+            $( __MatchErrKey::$variant $( ( $($inner)+ ) )? )|+ $( if $guard )? => $arm,
+        ] $($($rest)*)?)
+    );
+
+    ( $bound:ident => $key:expr, [$($path:tt)*], $any:expr, { $($arms:tt)* } ) => (
+        $crate::__match_err_keyed!(@arms [$bound => $key] [$($path)*] ($any) [] $($arms)*)
+    );
+}
+
+/// Returns the variant name of an error as a `Cow<'static, str>`, from [`VariantName`] when the
+/// type implements it and from its `Debug` rendering otherwise
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __match_err_variant_name {
+    ($e:expr) => ({
+        #[allow(unused_imports)]
+        use $crate::__private::{ViaDebug as _, ViaVariantName as _};
+        (&$crate::__private::NameProbe($e)).match_err_variant_name()
+    });
+}
+
+/// Joins a bracketed type path with a variant and its payload, so the type can be reused inside
+/// the arm repetitions of the matching macros
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __match_err_path {
+    ([$($ty:tt)*]) => ( $($ty)* );
+    ([$($ty:tt)*] $variant:ident $($rest:tt)*) => ( $($ty)*::$variant $($rest)* );
 }
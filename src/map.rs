@@ -0,0 +1,186 @@
+/// Maps the selected variants of the error to a new error, other errors are passed through.
+/// The new error is converted into the error type of the result with `Into`
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum StoreError {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("conflict on {0}")]
+///     Conflict(u64),
+///     #[error("corrupted")]
+///     Corrupted,
+///  }
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum ApiError {
+///     #[error("missing")]
+///     Missing,
+///     #[error("duplicate {0}")]
+///     Duplicate(u64),
+///  }
+///
+///  fn get(err: StoreError) -> anyhow::Result<()> {
+///     map_err_variant!(Err(anyhow!(err)), StoreError, {
+///         NotFound => ApiError::Missing,
+///         Conflict(id) => ApiError::Duplicate(*id)
+///     })
+///  }
+///
+///  assert_if_error!(get(StoreError::NotFound), ApiError, Missing);
+///  assert_if_error!(get(StoreError::Conflict(7)), ApiError, Duplicate(7));
+///  assert_if_error!(get(StoreError::Corrupted), StoreError, Corrupted);
+///
+///  let res: anyhow::Result<u32> = map_err_variant!(Ok(1), StoreError, { NotFound => ApiError::Missing });
+///  assert_eq!(res.unwrap(), 1);
+/// ```
+#[macro_export]
+macro_rules! map_err_variant {
+    ( $res:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $( $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $arm:expr ),* $(,)? } ) => (
+        match $res {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                let mapped = $crate::match_err!(err, $($ty)::+ $(::<$($gen),+>)?, {
+                    $( $( #[$attr] )* $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => Some($arm), )*
+                    _ => None
+                });
+                match mapped {
+                    Some(mapped) => Err(::core::convert::Into::into(mapped)),
+                    None => Err(err),
+                }
+            }
+        }
+    );
+}
+
+/// Converts an error of one enum-like type into another by mapping its variants, like an internal
+/// error into the error of a public API. The arms map the variants of the first type to variants
+/// of the second, both without the type. They are a native `match` on the error, so leaving a
+/// variant out without a `_` arm at the end fails to compile, and the payloads are moved out of it.
+/// With `impl` in front of the types the macro implements `From` with the mapping instead, so `?`
+/// converts the errors
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum DbError {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("conflict on {0}")]
+///     Conflict(u64),
+///     #[error("pool exhausted after {waited}ms")]
+///     Exhausted { waited: u64 },
+///     #[error("corrupted")]
+///     Corrupted,
+///  }
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum ApiError {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("duplicate {0}")]
+///     Duplicate(u64),
+///     #[error("retry after {secs}s")]
+///     Unavailable { secs: u64 },
+///     #[error("internal error")]
+///     Internal,
+///  }
+///
+///  let convert = |err: DbError| convert_err!(err, DbError => ApiError, {
+///     NotFound => NotFound,
+///     Conflict(id) => Duplicate(id),
+///     Exhausted { waited } if waited > 1000 => Unavailable { secs: waited / 1000 },
+///     _ => Internal
+///  });
+///
+///  assert_eq!(convert(DbError::NotFound), ApiError::NotFound);
+///  assert_eq!(convert(DbError::Conflict(7)), ApiError::Duplicate(7));
+///  assert_eq!(convert(DbError::Exhausted { waited: 3000 }), ApiError::Unavailable { secs: 3 });
+///  assert_eq!(convert(DbError::Exhausted { waited: 10 }), ApiError::Internal);
+///  assert_eq!(convert(DbError::Corrupted), ApiError::Internal);
+///
+///  #[derive(thiserror::Error, Debug, PartialEq)]
+///  enum QueueError {
+///     #[error("closed")]
+///     Closed,
+///     #[error("full")]
+///     Full,
+///  }
+///
+///  convert_err!(impl QueueError => ApiError, {
+///     Closed => Internal,
+///     Full => Unavailable { secs: 1 },
+///  });
+///
+///  fn push(res: Result<(), QueueError>) -> Result<(), ApiError> {
+///     res?;
+///     Ok(())
+///  }
+///  assert_eq!(push(Err(QueueError::Full)), Err(ApiError::Unavailable { secs: 1 }));
+/// ```
+///
+/// A variant that isn't mapped fails to compile
+/// ```compile_fail
+///  use match_err::*;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum DbError {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("corrupted")]
+///     Corrupted,
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum ApiError {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let err = convert_err!(DbError::Corrupted, DbError => ApiError, { NotFound => NotFound });
+/// ```
+#[macro_export]
+macro_rules! convert_err {
+    (@arms $err:ident $from:tt $to:tt [$($arms:tt)*] _ => $variant:ident $( ( $($args:tt)* ) )? $( { $($values:tt)* } )? $(,)?) => (
+        $crate::convert_err!(@match $err [
+            $($arms)*
+            _ => $crate::__match_err_path!($to $variant $( ( $($args)* ) )? $( { $($values)* } )?),
+        ])
+    );
+
+    (@arms $err:ident $from:tt $to:tt [$($arms:tt)*] $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $into:ident $( ( $($args:tt)* ) )? $( { $($values:tt)* } )? $(, $($rest:tt)*)?) => (
+        $crate::convert_err!(@arms $err $from $to [
+            $($arms)*
+            $( #[$attr] )* $( $crate::__match_err_path!($from $variant $( ( $($inner)+ ) )? $( { $($fields)* } )?) )|+ $( if $guard )? => $crate::__match_err_path!($to $into $( ( $($args)* ) )? $( { $($values)* } )?),
+        ] $($($rest)*)?)
+    );
+
+    (@arms $err:ident $from:tt $to:tt [$($arms:tt)*]) => (
+        $crate::convert_err!(@match $err [$($arms)*])
+    );
+
+    (@match $err:ident [$($arms:tt)*]) => (
+        match $err {
+            $($arms)*
+        }
+    );
+
+    ( impl $($from:ident)::+ $(::)? $(< $($fgen:ty),+ $(,)? >)? => $($to:ident)::+ $(::)? $(< $($tgen:ty),+ $(,)? >)?, { $($arms:tt)* } $(,)? ) => (
+        impl ::core::convert::From<$($from)::+ $(<$($fgen),+>)?> for $($to)::+ $(<$($tgen),+>)? {
+            fn from(err: $($from)::+ $(<$($fgen),+>)?) -> Self {
+                $crate::convert_err!(@arms err [$($from)::+ $(::<$($fgen),+>)?] [$($to)::+ $(::<$($tgen),+>)?] [] $($arms)*)
+            }
+        }
+    );
+
+    ( $err:expr, $($from:ident)::+ $(::)? $(< $($fgen:ty),+ $(,)? >)? => $($to:ident)::+ $(::)? $(< $($tgen:ty),+ $(,)? >)?, { $($arms:tt)* } $(,)? ) => ({
+        let err: $($from)::+ $(<$($fgen),+>)? = $err;
+        $crate::convert_err!(@arms err [$($from)::+ $(::<$($fgen),+>)?] [$($to)::+ $(::<$($tgen),+>)?] [] $($arms)*)
+    });
+}
@@ -0,0 +1,127 @@
+//! [googletest](https://docs.rs/googletest) matchers for errors, so they compose with `assert_that!`
+//! and `expect_that!`. Requires the `googletest` feature
+//!
+//! # Examples
+//! ```
+//! # #[cfg(feature = "googletest")]
+//! # {
+//!  use googletest::prelude::*;
+//!  use match_err::matchers::{err_matching, err_variant};
+//!  use anyhow::anyhow;
+//!
+//!  #[derive(thiserror::Error, Debug, PartialEq)]
+//!  enum Error {
+//!     #[error("not found")]
+//!     NotFound,
+//!     #[error("custom: {0}")]
+//!     Custom(String),
+//!  }
+//!
+//!  let err = anyhow!(Error::NotFound);
+//!  assert_that!(err, err_variant(Error::NotFound));
+//!  assert_that!(err, not(err_variant(Error::Custom(String::from("a")))));
+//!
+//!  let err = anyhow!(Error::Custom(String::from("disk is full")));
+//!  assert_that!(err, err_matching(|e: &Error| matches!(e, Error::Custom(msg) if msg.contains("disk"))));
+//!  assert_that!(err, err_variant(Error::NotFound).or(err_matching(|e: &Error| matches!(e, Error::Custom(_)))));
+//! # }
+//! ```
+
+use crate::{Downcastable, Mismatch};
+use core::any::type_name;
+use core::fmt::Debug;
+use googletest::description::Description;
+use googletest::matcher::{Matcher, MatcherBase, MatcherResult};
+use std::error::Error;
+
+/// Matches errors that downcast to a value equal to `expected`
+pub fn err_variant<E>(expected: E) -> ErrVariantMatcher<E>
+where
+    E: Error + PartialEq + Send + Sync + 'static,
+{
+    ErrVariantMatcher { expected }
+}
+
+/// Matches errors that downcast to `E` and satisfy the predicate
+pub fn err_matching<E, P>(predicate: P) -> ErrMatchingMatcher<E, P>
+where
+    E: Error + Send + Sync + 'static,
+    P: Fn(&E) -> bool,
+{
+    ErrMatchingMatcher {
+        predicate,
+        _error: core::marker::PhantomData,
+    }
+}
+
+/// The matcher returned by [`err_variant`]
+#[derive(Debug)]
+pub struct ErrVariantMatcher<E> {
+    expected: E,
+}
+
+impl<E> MatcherBase for ErrVariantMatcher<E> {}
+
+impl<D, E> Matcher<&D> for ErrVariantMatcher<E>
+where
+    D: Downcastable + Debug + ?Sized,
+    E: Error + PartialEq + Send + Sync + 'static,
+{
+    fn matches(&self, actual: &D) -> MatcherResult {
+        (actual.downcast_ref::<E>() == Some(&self.expected)).into()
+    }
+
+    fn describe(&self, sense: MatcherResult) -> Description {
+        match sense {
+            MatcherResult::Match => format!("is an error equal to {:?}", self.expected).into(),
+            MatcherResult::NoMatch => format!("isn't an error equal to {:?}", self.expected).into(),
+        }
+    }
+
+    fn explain_match(&self, actual: &D) -> Description {
+        match actual.downcast_ref::<E>() {
+            None => format!("which isn't of type {}", type_name::<E>()).into(),
+            Some(e) => match Mismatch::of(e, &self.expected) {
+                None => format!("which is {e:?}").into(),
+                Some(Mismatch::Payload) => format!("which is {e:?}, a different payload").into(),
+                Some(_) => format!("which is {e:?}, a different variant").into(),
+            },
+        }
+    }
+}
+
+/// The matcher returned by [`err_matching`]
+pub struct ErrMatchingMatcher<E, P> {
+    predicate: P,
+    _error: core::marker::PhantomData<fn(&E)>,
+}
+
+impl<E, P> MatcherBase for ErrMatchingMatcher<E, P> {}
+
+impl<D, E, P> Matcher<&D> for ErrMatchingMatcher<E, P>
+where
+    D: Downcastable + Debug + ?Sized,
+    E: Error + Send + Sync + 'static,
+    P: Fn(&E) -> bool,
+{
+    fn matches(&self, actual: &D) -> MatcherResult {
+        actual.downcast_ref::<E>().is_some_and(&self.predicate).into()
+    }
+
+    fn describe(&self, sense: MatcherResult) -> Description {
+        match sense {
+            MatcherResult::Match => format!("is an error of type {} matching the predicate", type_name::<E>()).into(),
+            MatcherResult::NoMatch => {
+                format!("isn't an error of type {} matching the predicate", type_name::<E>()).into()
+            }
+        }
+    }
+
+    fn explain_match(&self, actual: &D) -> Description {
+        match actual.downcast_ref::<E>() {
+            None => format!("which isn't of type {}", type_name::<E>()).into(),
+            Some(e) if (self.predicate)(e) => format!("which is {e:?}").into(),
+            Some(e) => format!("which is {e:?}, not matching the predicate").into(),
+        }
+    }
+}
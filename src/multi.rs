@@ -0,0 +1,180 @@
+use alloc::vec::{self, Vec};
+use core::error::Error;
+use core::fmt::{self, Debug, Display};
+use core::slice;
+
+/// A batch of errors, like the failures of jobs run together, that can be triaged at once with
+/// [`match_errs!`](crate::match_errs). Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  let results = vec![Ok(1), Err(anyhow!("disk full")), Ok(2), Err(anyhow!("timeout"))];
+///  let errors = MultiError::from_results(results).unwrap_err();
+///
+///  assert_eq!(errors.len(), 2);
+///  assert_eq!(errors.to_string(), "2 errors: disk full; timeout");
+///
+///  let results: Vec<anyhow::Result<u32>> = vec![Ok(1), Ok(2)];
+///  assert_eq!(MultiError::from_results(results).unwrap(), [1, 2]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiError<E> {
+    errors: Vec<E>,
+}
+
+impl<E> MultiError<E> {
+    /// Creates an empty batch
+    pub fn new() -> Self {
+        MultiError { errors: Vec::new() }
+    }
+
+    /// Returns the values of the results if all of them are `Ok`, otherwise every error
+    pub fn from_results<T, I: IntoIterator<Item = Result<T, E>>>(results: I) -> Result<Vec<T>, Self> {
+        let mut values = Vec::new();
+        let mut errors = MultiError::new();
+        for res in results {
+            match res {
+                Ok(value) => values.push(value),
+                Err(err) => errors.push(err),
+            }
+        }
+        if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Adds an error to the batch
+    pub fn push(&mut self, err: E) {
+        self.errors.push(err);
+    }
+
+    /// Returns the number of errors
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Returns `true` if there are no errors
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Iterates over the errors in the order they were added
+    pub fn iter(&self) -> slice::Iter<'_, E> {
+        self.errors.iter()
+    }
+
+    /// Returns the errors
+    pub fn into_vec(self) -> Vec<E> {
+        self.errors
+    }
+}
+
+impl<E> Default for MultiError<E> {
+    fn default() -> Self {
+        MultiError::new()
+    }
+}
+
+impl<E> From<Vec<E>> for MultiError<E> {
+    fn from(errors: Vec<E>) -> Self {
+        MultiError { errors }
+    }
+}
+
+impl<E> FromIterator<E> for MultiError<E> {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        MultiError { errors: iter.into_iter().collect() }
+    }
+}
+
+impl<E> Extend<E> for MultiError<E> {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        self.errors.extend(iter);
+    }
+}
+
+impl<E> IntoIterator for MultiError<E> {
+    type Item = E;
+    type IntoIter = vec::IntoIter<E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+impl<'a, E> IntoIterator for &'a MultiError<E> {
+    type Item = &'a E;
+    type IntoIter = slice::Iter<'a, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.iter()
+    }
+}
+
+impl<E: Display> Display for MultiError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errors.len() {
+            1 => f.write_str("1 error: ")?,
+            n => write!(f, "{n} errors: ")?,
+        }
+        for (i, err) in self.errors.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{err}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: Debug + Display> Error for MultiError<E> {}
+
+/// Matches every error of a batch like [`match_err!`](crate::match_err) and collects the results of
+/// the arms into a `Vec`, in the order of the errors. Takes anything iterable over errors, like a
+/// [`MultiError`], a reference to one, or a `Vec` of errors. Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("timeout after {0}s")]
+///     Timeout(u64),
+///  }
+///
+///  let errors: MultiError<anyhow::Error> = vec![
+///     anyhow!(Error::NotFound),
+///     anyhow!(Error::Timeout(5)),
+///     anyhow!("disk full"),
+///     anyhow!(Error::Timeout(10)),
+///  ].into_iter().collect();
+///
+///  let triaged = match_errs!(&errors, Error, {
+///     NotFound => "skip",
+///     Timeout(secs) if *secs < 10 => "retry",
+///     _ => "page"
+///  });
+///  assert_eq!(triaged, ["skip", "retry", "page", "page"]);
+///
+///  let retries = match_errs!(errors, Error, { Timeout(_) => 1, _ => 0 }).into_iter().sum::<u32>();
+///  assert_eq!(retries, 2);
+/// ```
+#[macro_export]
+macro_rules! match_errs {
+    ( $errors:expr, $($rest:tt)+ ) => ({
+        let mut matched = $crate::__private::Vec::new();
+        for err in $crate::__private::IntoIterator::into_iter($errors) {
+            matched.push($crate::match_err!(err, $($rest)+));
+        }
+        matched
+    });
+}
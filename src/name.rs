@@ -0,0 +1,100 @@
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+#[cfg(feature = "alloc")]
+use alloc::format;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use core::fmt::Debug;
+
+/// The name of the variant of an enum-like error type, used as the key of [`ErrorStats`](crate::ErrorStats)
+/// and [`PartitionExt`](crate::PartitionExt) and as the variant field of traces and metrics.
+/// Implemented by the [`MatchErr`](derive@crate::MatchErr) derive
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///
+///  #[derive(Debug)]
+///  enum Error {
+///     NotFound,
+///     Timeout { secs: u64 },
+///  }
+///
+///  impl VariantName for Error {
+///     fn variant_name(&self) -> &'static str {
+///         match self {
+///             Error::NotFound => "NotFound",
+///             Error::Timeout { .. } => "Timeout",
+///         }
+///     }
+///  }
+///
+///  assert_eq!(Error::Timeout { secs: 5 }.variant_name(), "Timeout");
+/// ```
+pub trait VariantName {
+    /// Returns the name of the variant
+    fn variant_name(&self) -> &'static str;
+}
+
+impl<V: VariantName + ?Sized> VariantName for &V {
+    fn variant_name(&self) -> &'static str {
+        (**self).variant_name()
+    }
+}
+
+/// Returns the name of the variant from the derived `Debug` rendering of an enum. Only a fallback
+/// for types without [`VariantName`]: it formats the whole payload, and gives a wrong name for a
+/// hand-written `Debug` that doesn't start with the variant
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub fn variant_name<E: Debug + ?Sized>(e: &E) -> String {
+    let mut name = format!("{e:?}");
+    if let Some(end) = name.find(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        name.truncate(end);
+    }
+    name
+}
+
+/// Wraps the error whose variant name the macros need, so they can fall back from [`VariantName`]
+/// to the `Debug` rendering through autoref, like [`Probe`](crate::__private::Probe) does
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub struct NameProbe<'a, E: ?Sized>(pub &'a E);
+
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub trait ViaVariantName {
+    fn match_err_variant_name(&self) -> Cow<'static, str>;
+}
+
+#[cfg(feature = "alloc")]
+impl<E: VariantName + ?Sized> ViaVariantName for NameProbe<'_, E> {
+    fn match_err_variant_name(&self) -> Cow<'static, str> {
+        Cow::Borrowed(self.0.variant_name())
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub trait ViaDebug {
+    fn match_err_variant_name(&self) -> Cow<'static, str>;
+}
+
+#[cfg(feature = "alloc")]
+impl<E: Debug + ?Sized> ViaDebug for &NameProbe<'_, E> {
+    fn match_err_variant_name(&self) -> Cow<'static, str> {
+        Cow::Owned(variant_name(self.0))
+    }
+}
+
+/// Returns the name of the type without its module path
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub fn type_name<T: ?Sized>() -> &'static str {
+    let name = core::any::type_name::<T>();
+    match name.find('<') {
+        Some(generics) => &name[name[..generics].rfind("::").map_or(0, |i| i + 2)..],
+        None => &name[name.rfind("::").map_or(0, |i| i + 2)..],
+    }
+}
@@ -0,0 +1,138 @@
+//! Classification of a `reqwest::Error` found anywhere in the cause chain, as predicates and
+//! through [`match_reqwest_err!`](crate::match_reqwest_err). Requires the `reqwest` feature
+//!
+//! # Examples
+//! ```
+//! # #[cfg(feature = "reqwest")]
+//! # {
+//!  use match_err::net::{self, ReqwestErrorKind};
+//!  use anyhow::Context;
+//!  use reqwest::StatusCode;
+//!
+//!  let response = reqwest::Response::from(http::Response::builder().status(503).body("").unwrap());
+//!  let err = response.error_for_status().context("fetching users").unwrap_err();
+//!
+//!  assert_eq!(net::classify(&err), Some(ReqwestErrorKind::Status(StatusCode::SERVICE_UNAVAILABLE)));
+//!  assert_eq!(net::status(&err), Some(StatusCode::SERVICE_UNAVAILABLE));
+//!  assert!(!net::is_timeout(&err));
+//!  assert_eq!(net::classify(&anyhow::anyhow!("unknown")), None);
+//! # }
+//! ```
+
+use crate::Downcastable;
+use reqwest::StatusCode;
+use std::error::Error;
+
+/// The kind of failure behind a `reqwest::Error`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReqwestErrorKind {
+    /// The request or connection timed out
+    Timeout,
+    /// The host name couldn't be resolved
+    Dns,
+    /// The connection couldn't be established
+    Connect,
+    /// The response has an error status, from `error_for_status`
+    Status(StatusCode),
+    /// The request or response body failed
+    Body,
+    /// The response body couldn't be decoded
+    Decode,
+    /// Any other failure, like building the request or following redirects
+    Other,
+}
+
+/// Returns the first `reqwest::Error` in the cause chain
+pub fn find_reqwest_error<D: Downcastable + ?Sized>(err: &D) -> Option<&reqwest::Error> {
+    err.find_in_chain::<reqwest::Error>()
+}
+
+/// Classifies the first `reqwest::Error` in the cause chain
+pub fn classify<D: Downcastable + ?Sized>(err: &D) -> Option<ReqwestErrorKind> {
+    find_reqwest_error(err).map(classify_reqwest)
+}
+
+/// Classifies a `reqwest::Error`. Timeouts take precedence over connection failures, and
+/// DNS failures are recognized by the `dns error` message hyper reports them with
+pub fn classify_reqwest(err: &reqwest::Error) -> ReqwestErrorKind {
+    if err.is_timeout() {
+        ReqwestErrorKind::Timeout
+    } else if err.is_connect() {
+        let mut source = err.source();
+        while let Some(e) = source {
+            if e.to_string().starts_with("dns error") {
+                return ReqwestErrorKind::Dns;
+            }
+            source = e.source();
+        }
+        ReqwestErrorKind::Connect
+    } else if let Some(status) = err.status() {
+        ReqwestErrorKind::Status(status)
+    } else if err.is_body() {
+        ReqwestErrorKind::Body
+    } else if err.is_decode() {
+        ReqwestErrorKind::Decode
+    } else {
+        ReqwestErrorKind::Other
+    }
+}
+
+/// Returns `true` if the request or connection timed out
+pub fn is_timeout<D: Downcastable + ?Sized>(err: &D) -> bool {
+    classify(err) == Some(ReqwestErrorKind::Timeout)
+}
+
+/// Returns `true` if the host name couldn't be resolved
+pub fn is_dns<D: Downcastable + ?Sized>(err: &D) -> bool {
+    classify(err) == Some(ReqwestErrorKind::Dns)
+}
+
+/// Returns `true` if the connection couldn't be established, DNS failures included
+pub fn is_connect<D: Downcastable + ?Sized>(err: &D) -> bool {
+    find_reqwest_error(err).is_some_and(reqwest::Error::is_connect)
+}
+
+/// Returns the error status of the response
+pub fn status<D: Downcastable + ?Sized>(err: &D) -> Option<StatusCode> {
+    find_reqwest_error(err)?.status()
+}
+
+/// Returns `true` if the request or response body failed
+pub fn is_body<D: Downcastable + ?Sized>(err: &D) -> bool {
+    classify(err) == Some(ReqwestErrorKind::Body)
+}
+
+/// Matches the first `reqwest::Error` in the cause chain by its [`ReqwestErrorKind`](crate::net::ReqwestErrorKind).
+/// The fallback arm handles other kinds and errors without a reqwest error, like in [`match_err!`].
+/// Requires the `reqwest` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "reqwest")]
+/// # {
+///  use match_err::*;
+///  use reqwest::StatusCode;
+///
+///  let retry = |err: anyhow::Error| match_reqwest_err!(err, {
+///     Timeout | Connect | Dns => true,
+///     Status(StatusCode::TOO_MANY_REQUESTS) => true,
+///     Status(code) if code.is_server_error() => true,
+///     _ => false
+///  });
+///
+///  let status = |code: u16| {
+///     let response = reqwest::Response::from(http::Response::builder().status(code).body("").unwrap());
+///     anyhow::Error::from(response.error_for_status().unwrap_err())
+///  };
+///  assert!(retry(status(503)));
+///  assert!(retry(status(429)));
+///  assert!(!retry(status(404)));
+///  assert!(!retry(anyhow::anyhow!("unknown")));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! match_reqwest_err {
+    ( $any:expr, { $($arms:tt)* } ) => (
+        $crate::__match_err_keyed!(any => $crate::net::classify(any), [$crate::net::ReqwestErrorKind], $any, { $($arms)* })
+    );
+}
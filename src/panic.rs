@@ -0,0 +1,208 @@
+use core::any::Any;
+use std::boxed::Box;
+use std::string::String;
+
+/// Returns the message of a panic payload, for panics with a string literal or a formatted message
+///
+/// # Examples
+/// ```
+///  use match_err::panic_message;
+///
+///  let payload = std::panic::catch_unwind(|| panic!("worker {} crashed", 3)).unwrap_err();
+///  assert_eq!(panic_message(&*payload), Some("worker 3 crashed"));
+///
+///  let payload = std::panic::catch_unwind(|| std::panic::panic_any(42)).unwrap_err();
+///  assert_eq!(panic_message(&*payload), None);
+/// ```
+pub fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    payload
+        .downcast_ref::<&'static str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+}
+
+/// A panic payload of `catch_unwind` or `JoinHandle::join`, owned or borrowed
+#[doc(hidden)]
+pub trait AsPanicPayload {
+    fn as_panic_payload(&self) -> &(dyn Any + Send);
+}
+
+impl AsPanicPayload for dyn Any + Send {
+    fn as_panic_payload(&self) -> &(dyn Any + Send) {
+        self
+    }
+}
+
+impl AsPanicPayload for Box<dyn Any + Send> {
+    fn as_panic_payload(&self) -> &(dyn Any + Send) {
+        &**self
+    }
+}
+
+impl<T: AsPanicPayload + ?Sized> AsPanicPayload for &T {
+    fn as_panic_payload(&self) -> &(dyn Any + Send) {
+        (**self).as_panic_payload()
+    }
+}
+
+/// Matches a panic payload, like the error of `std::panic::catch_unwind` or of joining a thread,
+/// against the types it can be downcast to. An arm is a type with either an expression or a closure
+/// taking a reference to the payload, `&str` and `String` are the payloads of `panic!` with a
+/// literal and with a formatted message. The fallback arm can bind the payload with `p @ _`.
+/// Requires the `std` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///
+///  #[derive(Debug)]
+///  struct Abort {
+///     code: i32,
+///  }
+///
+///  let describe = |f: fn()| match_panic!(std::panic::catch_unwind(f).unwrap_err(), {
+///     &str => |msg| format!("panicked: {msg}"),
+///     String => |msg| format!("panicked: {msg}"),
+///     Abort => |abort| format!("aborted with {}", abort.code),
+///     i32 => String::from("panicked with a number"),
+///     p @ _ => format!("unknown payload, u8: {}", p.is::<u8>())
+///  });
+///
+///  assert_eq!(describe(|| panic!("boom")), "panicked: boom");
+///  assert_eq!(describe(|| panic!("worker {} crashed", 3)), "panicked: worker 3 crashed");
+///  assert_eq!(describe(|| std::panic::panic_any(Abort { code: 2 })), "aborted with 2");
+///  assert_eq!(describe(|| std::panic::panic_any(42)), "panicked with a number");
+///  assert_eq!(describe(|| std::panic::panic_any(1u8)), "unknown payload, u8: true");
+///
+///  let payload = std::thread::spawn(|| panic!("thread crashed")).join().unwrap_err();
+///  let msg = match_panic!(&payload, { &str => |msg| *msg, _ => "unknown" });
+///  assert_eq!(msg, "thread crashed");
+/// ```
+#[macro_export]
+macro_rules! match_panic {
+    (@match $payload:tt [$( ($ty:ty, $bind:pat_param, $arm:expr) )*] [$($fallback:ident)?] $default:expr) => (
+        match $crate::__private::AsPanicPayload::as_panic_payload(&$payload) {
+            payload => 'matched: {
+                $(
+                    if let Some(p) = payload.downcast_ref::<$ty>() {
+                        let $bind = p;
+                        break 'matched $arm;
+                    }
+                )*
+                $( let $fallback = payload; )?
+                $default
+            }
+        }
+    );
+
+    (@arms $payload:tt [$($arms:tt)*] _ => $default:expr $(,)?) => (
+        $crate::match_panic!(@match $payload [$($arms)*] [] $default)
+    );
+
+    (@arms $payload:tt [$($arms:tt)*] $bind:ident @ _ => $default:expr $(,)?) => (
+        $crate::match_panic!(@match $payload [$($arms)*] [$bind] $default)
+    );
+
+    (@arms $payload:tt [$($arms:tt)*] $(,)?) => (
+        $crate::match_panic!(@match $payload [$($arms)*] [] {})
+    );
+
+    (@arms $payload:tt [$($arms:tt)*] $ty:ty => |$bind:pat_param| $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_panic!(@arms $payload [$($arms)* ($ty, $bind, $arm)] $($($rest)*)?)
+    );
+
+    (@arms $payload:tt [$($arms:tt)*] $ty:ty => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_panic!(@arms $payload [$($arms)* ($ty, _, $arm)] $($($rest)*)?)
+    );
+
+    ( $payload:expr, { $($arms:tt)* } $(,)? ) => (
+        $crate::match_panic!(@arms ($payload) [] $($arms)*)
+    );
+}
+
+/// Asserts that the closure panics, with a message containing the string like
+/// `#[should_panic(expected = ..)]`, or with a payload of the type that optionally matches a
+/// pattern. With a type it evaluates to the payload. Requires the `std` feature
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///
+///  #[derive(Debug, PartialEq)]
+///  struct Abort {
+///     code: i32,
+///  }
+///
+///  assert_panics_with!(|| panic!("worker {} crashed", 3), "crashed");
+///
+///  let abort = assert_panics_with!(|| std::panic::panic_any(Abort { code: 2 }), Abort);
+///  assert_eq!(abort, Abort { code: 2 });
+///  assert_panics_with!(|| std::panic::panic_any(Abort { code: 2 }), Abort, Abort { code } if *code > 1);
+///
+///  let panic = std::panic::catch_unwind(|| assert_panics_with!(|| panic!("boom"), "crashed")).unwrap_err();
+///  assert_eq!(
+///     panic_message(&*panic),
+///     Some("assertion failed: expected a panic with \"crashed\", got \"boom\""),
+///  );
+///
+///  let panic = std::panic::catch_unwind(|| assert_panics_with!(|| (), Abort)).unwrap_err();
+///  assert_eq!(panic_message(&*panic), Some("assertion failed: expected a panic, the closure returned"));
+/// ```
+#[macro_export]
+macro_rules! assert_panics_with {
+    (@catch $f:expr) => (
+        match ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe($f)) {
+            Ok(_) => $crate::__private::assert_failed(
+                "assert_panics_with",
+                format_args!("assertion failed: expected a panic, the closure returned"),
+            ),
+            Err(payload) => payload,
+        }
+    );
+
+    ($f:expr, $msg:literal $(,)?) => ({
+        let payload = $crate::assert_panics_with!(@catch $f);
+        match $crate::panic_message(&*payload) {
+            Some(msg) if msg.contains($msg) => {}
+            Some(msg) => $crate::__private::assert_failed(
+                "assert_panics_with",
+                format_args!("assertion failed: expected a panic with {:?}, got {:?}", $msg, msg),
+            ),
+            None => $crate::__private::assert_failed(
+                "assert_panics_with",
+                format_args!("assertion failed: expected a panic with {:?}, got a payload that isn't a string", $msg),
+            ),
+        }
+    });
+
+    ($f:expr, $ty:ty $(, $pat:pat $( if $guard:expr )?)? $(,)?) => ({
+        let payload = $crate::assert_panics_with!(@catch $f);
+        let payload: $ty = match payload.downcast::<$ty>() {
+            Ok(payload) => *payload,
+            Err(payload) => $crate::__private::assert_failed(
+                "assert_panics_with",
+                format_args!(
+                    "assertion failed: expected a panic with a `{}`, got {}",
+                    ::core::any::type_name::<$ty>(),
+                    match $crate::panic_message(&*payload) {
+                        Some(msg) => $crate::__private::format!("{:?}", msg),
+                        None => $crate::__private::String::from("a payload of another type"),
+                    },
+                ),
+            ),
+        };
+        $(
+            if !::core::matches!(&payload, $pat $( if $guard )?) {
+                $crate::__private::assert_failed(
+                    "assert_panics_with",
+                    format_args!(
+                        "assertion failed: expected a panic matching `{}`, got {:?}",
+                        stringify!($pat),
+                        payload,
+                    ),
+                );
+            }
+        )?
+        payload
+    });
+}
@@ -0,0 +1,71 @@
+use core::error::Error;
+use std::collections::HashMap;
+
+use crate::{Downcastable, VariantName};
+
+/// Triage of a batch of results, splitting them into the values, the errors of type `E` grouped by
+/// [`VariantName`], and the errors of other types. Requires the `std` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "macros")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum ImportError {
+///     #[error("duplicate row {0}")]
+///     Duplicate(u32),
+///     #[error("invalid row {row}")]
+///     Invalid { row: u32 },
+///  }
+///
+///  let results = vec![
+///     Ok(1),
+///     Err(anyhow!(ImportError::Duplicate(2))),
+///     Err(anyhow!(ImportError::Invalid { row: 3 })),
+///     Err(anyhow!("connection reset")),
+///     Err(anyhow!(ImportError::Duplicate(5))),
+///     Ok(6),
+///  ];
+///
+///  let (rows, by_variant, unmatched) = results.into_iter().partition_by_variant::<ImportError>();
+///  assert_eq!(rows, [1, 6]);
+///  assert_eq!(by_variant["Duplicate"].len(), 2);
+///  assert_eq!(by_variant["Invalid"].len(), 1);
+///  assert_eq!(unmatched[0].to_string(), "connection reset");
+/// # }
+/// ```
+pub trait PartitionExt<T, D>: Iterator<Item = Result<T, D>> + Sized {
+    /// Splits the results into the `Ok` values, the errors of type `E` keyed by variant name and the
+    /// remaining errors, all in the order of the iterator
+    fn partition_by_variant<E>(self) -> (Vec<T>, HashMap<&'static str, Vec<D>>, Vec<D>)
+    where
+        E: Error + Send + Sync + VariantName + 'static;
+}
+
+impl<I, T, D> PartitionExt<T, D> for I
+where
+    I: Iterator<Item = Result<T, D>>,
+    D: Downcastable,
+{
+    fn partition_by_variant<E>(self) -> (Vec<T>, HashMap<&'static str, Vec<D>>, Vec<D>)
+    where
+        E: Error + Send + Sync + VariantName + 'static,
+    {
+        let mut oks = Vec::new();
+        let mut by_variant: HashMap<&'static str, Vec<D>> = HashMap::new();
+        let mut unmatched = Vec::new();
+        for res in self {
+            match res {
+                Ok(value) => oks.push(value),
+                Err(err) => match err.downcast_ref::<E>().map(E::variant_name) {
+                    Some(variant) => by_variant.entry(variant).or_default().push(err),
+                    None => unmatched.push(err),
+                },
+            }
+        }
+        (oks, by_variant, unmatched)
+    }
+}
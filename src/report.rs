@@ -0,0 +1,77 @@
+use core::fmt::{self, Display, Write as _};
+use core::panic::Location;
+use std::io::Write as _;
+
+/// The environment variable enabling the JSON reports of failed assertions
+const ENV: &str = "MATCH_ERR_JSON";
+
+/// A field value of a JSON report
+pub(crate) enum Value<'a> {
+    Str(&'a str),
+    Opt(Option<&'a str>),
+    Num(usize),
+    List(&'a [String]),
+}
+
+/// Writes a failed assertion as a JSON object on a single line if `MATCH_ERR_JSON` is set, to stderr
+/// for `1` or `stderr` and appended to the file at the given path otherwise
+pub(crate) fn report(assertion: &str, fields: &[(&str, Value<'_>)], location: &Location<'_>) {
+    let Some(target) = std::env::var_os(ENV) else {
+        return;
+    };
+
+    let mut json = format!("{{\"assertion\":{}", Json(assertion));
+    for (name, value) in fields {
+        let _ = write!(json, ",{}:", Json(name));
+        let _ = match value {
+            Value::Str(value) | Value::Opt(Some(value)) => write!(json, "{}", Json(value)),
+            Value::Opt(None) => write!(json, "null"),
+            Value::Num(value) => write!(json, "{value}"),
+            Value::List(values) => {
+                json.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        json.push(',');
+                    }
+                    let _ = write!(json, "{}", Json(value));
+                }
+                json.push(']');
+                Ok(())
+            }
+        };
+    }
+    let _ = write!(
+        json,
+        ",\"file\":{},\"line\":{},\"column\":{}}}",
+        Json(location.file()),
+        location.line(),
+        location.column()
+    );
+
+    if target == "1" || target == "stderr" {
+        eprintln!("{json}");
+    } else if let Ok(mut file) = std::fs::OpenOptions::new().create(true).append(true).open(target) {
+        let _ = writeln!(file, "{json}");
+    }
+}
+
+/// Renders a string as a JSON string literal
+struct Json<'a>(&'a str);
+
+impl Display for Json<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
@@ -0,0 +1,208 @@
+use core::time::Duration;
+
+/// Delays between the attempts of [`retry_async_if_err!`]
+///
+/// # Examples
+/// ```
+///  use match_err::Backoff;
+///  use std::time::Duration;
+///
+///  let backoff = Backoff::fixed(Duration::from_millis(100), 3);
+///  assert_eq!(backoff.retries(), 3);
+///  assert_eq!(backoff.delay(2), Duration::from_millis(100));
+///
+///  let backoff = Backoff::exponential(Duration::from_millis(100), 5).max_delay(Duration::from_millis(500));
+///  assert_eq!(backoff.delay(0), Duration::from_millis(100));
+///  assert_eq!(backoff.delay(2), Duration::from_millis(400));
+///  assert_eq!(backoff.delay(3), Duration::from_millis(500));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    /// The same delay before every retry
+    Fixed { delay: Duration, retries: usize },
+    /// A delay doubling with every retry, up to `max`
+    Exponential {
+        initial: Duration,
+        max: Duration,
+        retries: usize,
+    },
+}
+
+impl Backoff {
+    /// Retries up to `retries` times waiting `delay` before each one
+    pub fn fixed(delay: Duration, retries: usize) -> Self {
+        Backoff::Fixed { delay, retries }
+    }
+
+    /// Retries up to `retries` times waiting `initial` before the first one and doubling it after
+    pub fn exponential(initial: Duration, retries: usize) -> Self {
+        Backoff::Exponential {
+            initial,
+            max: Duration::MAX,
+            retries,
+        }
+    }
+
+    /// Caps the delay of an exponential backoff
+    pub fn max_delay(self, max: Duration) -> Self {
+        match self {
+            Backoff::Exponential { initial, retries, .. } => Backoff::Exponential { initial, max, retries },
+            backoff => backoff,
+        }
+    }
+
+    /// The maximum number of retries
+    pub fn retries(&self) -> usize {
+        match *self {
+            Backoff::Fixed { retries, .. } | Backoff::Exponential { retries, .. } => retries,
+        }
+    }
+
+    /// The delay before the given retry, starting from `0`
+    pub fn delay(&self, retry: usize) -> Duration {
+        match *self {
+            Backoff::Fixed { delay, .. } => delay,
+            Backoff::Exponential { initial, max, .. } => {
+                let factor = u32::try_from(retry).ok().and_then(|retry| 2u32.checked_pow(retry));
+                factor.map_or(max, |factor| initial.saturating_mul(factor).min(max))
+            }
+        }
+    }
+}
+
+/// Evaluates the expression and re-evaluates it up to the given number of times while it returns
+/// an error that matches one of the listed variants. Returns the last result
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("timeout")]
+///     Timeout,
+///     #[error("rate limited for {0}s")]
+///     RateLimited(u64),
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let mut attempts = 0;
+///  let res = retry_if_err!(3, {
+///     attempts += 1;
+///     if attempts < 3 { Err(anyhow!(Error::Timeout)) } else { Ok(attempts) }
+///  }, Error, { Timeout, RateLimited(_) });
+///  assert_eq!(res.unwrap(), 3);
+///
+///  let mut attempts = 0;
+///  let res: Result<(), _> = retry_if_err!(3, {
+///     attempts += 1;
+///     Err(anyhow!(Error::RateLimited(1)))
+///  }, Error, { Timeout, RateLimited(_) });
+///  assert!(res.is_err());
+///  assert_eq!(attempts, 4);
+///
+///  let mut attempts = 0;
+///  let res: Result<(), _> = retry_if_err!(3, {
+///     attempts += 1;
+///     Err(anyhow!(Error::NotFound))
+///  }, Error, { Timeout | RateLimited(_) });
+///  assert!(res.is_err());
+///  assert_eq!(attempts, 1);
+/// ```
+#[macro_export]
+macro_rules! retry_if_err {
+    ( $retries:expr, $op:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $( $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ ),+ $(,)? } ) => ({
+        let mut retries: usize = $retries;
+        loop {
+            let res = $op;
+            let retryable = $crate::match_if_err!(res, $($ty)::+ $(::<$($gen),+>)?, {
+                $( $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ => true, )+
+                _ => false
+            });
+            if retryable && retries > 0 {
+                retries -= 1;
+                continue;
+            }
+            break res;
+        }
+    });
+}
+
+/// Async version of [`retry_if_err!`] waiting between the attempts according to a [`Backoff`].
+/// The sleep function is passed in, so any runtime can be used, e.g. `tokio::time::sleep`
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::cell::RefCell;
+///  use std::time::Duration;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("timeout")]
+///     Timeout,
+///     #[error("unavailable")]
+///     Unavailable,
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let delays = RefCell::new(Vec::new());
+///  let sleep = |delay: Duration| {
+///     delays.borrow_mut().push(delay);
+///     std::future::ready(())
+///  };
+///
+///  let mut attempts = 0;
+///  let mut request = || {
+///     attempts += 1;
+///     std::future::ready(if attempts < 4 { Err(anyhow!(Error::Unavailable)) } else { Ok(attempts) })
+///  };
+///
+///  let backoff = Backoff::exponential(Duration::from_millis(10), 5);
+///  let res = block_on(async {
+///     retry_async_if_err!(backoff, sleep, request().await, Error, { Timeout, Unavailable })
+///  });
+///  assert_eq!(res.unwrap(), 4);
+///  assert_eq!(*delays.borrow(), [10, 20, 40].map(Duration::from_millis));
+///
+///  let res: Result<(), _> = block_on(async {
+///     retry_async_if_err!(backoff, sleep, async { Err(anyhow!(Error::NotFound)) }.await, Error, { Timeout, Unavailable })
+///  });
+///  assert!(res.is_err());
+///  assert_eq!(delays.borrow().len(), 3);
+///
+///  # fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+///  #     struct Noop;
+///  #     impl std::task::Wake for Noop { fn wake(self: std::sync::Arc<Self>) {} }
+///  #     let waker = std::sync::Arc::new(Noop).into();
+///  #     let mut cx = std::task::Context::from_waker(&waker);
+///  #     let mut fut = std::pin::pin!(fut);
+///  #     loop {
+///  #         if let std::task::Poll::Ready(out) = fut.as_mut().poll(&mut cx) { return out; }
+///  #     }
+///  # }
+/// ```
+#[macro_export]
+macro_rules! retry_async_if_err {
+    ( $backoff:expr, $sleep:expr, $op:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $( $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ ),+ $(,)? } ) => ({
+        let backoff: $crate::Backoff = $backoff;
+        let mut retry: usize = 0;
+        loop {
+            let res = $op;
+            let retryable = $crate::match_if_err!(res, $($ty)::+ $(::<$($gen),+>)?, {
+                $( $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ => true, )+
+                _ => false
+            });
+            if retryable && retry < backoff.retries() {
+                ($sleep)(backoff.delay(retry)).await;
+                retry += 1;
+                continue;
+            }
+            break res;
+        }
+    });
+}
@@ -0,0 +1,276 @@
+//! Stable renderings of errors for snapshot tests, and
+//! [`assert_err_snapshot!`](crate::assert_err_snapshot) and
+//! [`assert_err_chain_snapshot!`](crate::assert_err_chain_snapshot) asserting them with
+//! [insta](https://docs.rs/insta). Only the layers of the cause chain that downcast to the error
+//! type given after the error get a variant, so the renderings don't depend on the `Debug` of other
+//! layers, and without a type none do. A snapshot is kept in a `.snap` file like
+//! `insta::assert_snapshot!` does, named after the test or with `name = "..";`, and an inline one
+//! is given after `@`. Requires the `insta` feature
+//!
+//! ```
+//! # #[cfg(feature = "insta")]
+//! # {
+//! use match_err::*;
+//! use anyhow::Context;
+//!
+//! #[derive(thiserror::Error, MatchErr, Debug)]
+//! enum Error {
+//!     #[error("user {0} not found")]
+//!     NotFound(u32),
+//! }
+//!
+//! fn load_user(id: u32) -> anyhow::Result<()> {
+//!     Err(Error::NotFound(id)).context("loading user")
+//! }
+//!
+//! let err = load_user(42).unwrap_err();
+//! assert_err_snapshot!(err, Error, @r"
+//! variant: NotFound
+//! payload: NotFound(
+//!     42,
+//! )
+//! chain:
+//!   0: loading user
+//!   1: user 42 not found
+//! ");
+//! assert_err_chain_snapshot!(err, Error, @r"
+//! 0: loading user
+//! 1: NotFound: user 42 not found
+//! ");
+//!
+//! fn missing_user() {
+//!     let err = load_user(42).unwrap_err();
+//!     assert_err_snapshot!(err);
+//!     assert_err_snapshot!(name = "missing_user_variant"; err, Error);
+//!     assert_err_chain_snapshot!(name = "missing_user_chain"; err, Error);
+//! }
+//! # }
+//! ```
+
+use core::error::Error;
+use core::fmt::{Debug, Write as _};
+
+use crate::downcast::downcast_layer;
+use crate::{Downcastable, ErrorChainIter, VariantName};
+
+/// Renders the variant and the pretty `Debug` payload of the error of type `E` in the cause chain,
+/// and the `Display` chain of the error. Without an `E` in the chain only the `Debug` of the error
+/// is rendered as the payload
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "insta")]
+/// # {
+///  use match_err::*;
+///  use anyhow::{anyhow, Context};
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum Error {
+///     #[error("timeout after {secs}s")]
+///     Timeout { secs: u64 },
+///  }
+///
+///  let res: Result<(), _> = Err(Error::Timeout { secs: 5 });
+///  let err = res.context("loading user").unwrap_err();
+///  assert_eq!(snapshot::render_err::<Error, _>(&err), "\
+/// variant: Timeout
+/// payload: Timeout {
+///     secs: 5,
+/// }
+/// chain:
+///   0: loading user
+///   1: timeout after 5s");
+///
+///  let err = anyhow!(std::io::Error::other("disk full"));
+///  assert_eq!(snapshot::render_err::<Error, _>(&err), "payload: Custom {
+///     kind: Other,
+///     error: \"disk full\",
+/// }
+/// chain:
+///   0: disk full");
+/// # }
+/// ```
+pub fn render_err<E, D>(err: &D) -> String
+where
+    E: Error + VariantName + Send + Sync + 'static,
+    D: Downcastable + Debug + ?Sized,
+{
+    let mut out = String::new();
+    match err.find_in_chain::<E>() {
+        Some(e) => {
+            let _ = write!(out, "variant: {}\npayload: {e:#?}", e.variant_name());
+        }
+        None => match err.to_dyn_error() {
+            Some(e) => {
+                let _ = write!(out, "payload: {e:#?}");
+            }
+            None => {
+                let _ = write!(out, "payload: {err:#?}");
+            }
+        },
+    }
+    if let Some(e) = err.to_dyn_error() {
+        out.push_str("\nchain:");
+        for (i, layer) in ErrorChainIter::new(e).enumerate() {
+            let _ = write!(out, "\n  {i}: {layer}");
+        }
+    }
+    out
+}
+
+/// Renders every layer of the cause chain as its `Display`, one per line, preceded by the variant
+/// for the layers of type `E`
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "insta")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum ApiError {
+///     #[error("upstream failed")]
+///     Upstream(#[source] Error),
+///  }
+///
+///  let err = anyhow!(ApiError::Upstream(Error::NotFound));
+///  assert_eq!(snapshot::render_chain::<Error, _>(&err), "0: upstream failed\n1: NotFound: not found");
+/// # }
+/// ```
+pub fn render_chain<E, D>(err: &D) -> String
+where
+    E: Error + VariantName + Send + Sync + 'static,
+    D: Downcastable + ?Sized,
+{
+    let mut out = String::new();
+    for (i, layer) in ErrorChainIter::of(err).enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        match downcast_layer::<E>(layer) {
+            Some(e) => {
+                let _ = write!(out, "{i}: {}: {layer}", e.variant_name());
+            }
+            None => {
+                let _ = write!(out, "{i}: {layer}");
+            }
+        }
+    }
+    out
+}
+
+/// The error type of the snapshot macros without a type, which no layer downcasts to
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum Untyped {}
+
+impl core::fmt::Display for Untyped {
+    fn fmt(&self, _: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {}
+    }
+}
+
+impl Error for Untyped {}
+
+impl VariantName for Untyped {
+    fn variant_name(&self) -> &'static str {
+        match *self {}
+    }
+}
+
+/// Snapshots [`render_err`] of an error with `insta::assert_snapshot!`. The error type naming the
+/// variant is optional, without it only the payload and the chain are rendered. The snapshot is
+/// inline after `@`, or kept in a `.snap` file, named after the test or by a leading
+/// `name = "..";`. Requires the `insta` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "insta")]
+/// # {
+///  use match_err::*;
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum Wrapper<T: std::fmt::Debug> {
+///     #[error("wrapped {0:?}")]
+///     Wrapped(T),
+///  }
+///
+///  let err = anyhow::anyhow!(Wrapper::Wrapped(7u8));
+///  assert_err_snapshot!(err, Wrapper<u8>, @r"
+///  variant: Wrapped
+///  payload: Wrapped(
+///      7,
+///  )
+///  chain:
+///    0: wrapped 7
+///  ");
+///  assert_err_chain_snapshot!(err, Wrapper<u8>, @"0: Wrapped: wrapped 7");
+///  assert_err_chain_snapshot!(err, @"0: wrapped 7");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_err_snapshot {
+    (@assert $render:ident [$($name:expr)?] $err:expr, [$($ty:tt)*] $(, @$snapshot:literal)?) => (
+        $crate::__private::insta::assert_snapshot!($($name,)? $crate::snapshot::$render::<$($ty)*, _>(&$err) $(, @$snapshot)?)
+    );
+
+    (name = $name:expr; $err:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)? $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_err [$name] $err, [$($ty)::+ $(::<$($gen),+>)?])
+    );
+
+    (name = $name:expr; $err:expr $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_err [$name] $err, [$crate::snapshot::Untyped])
+    );
+
+    ($err:expr, @$snapshot:literal $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_err [] $err, [$crate::snapshot::Untyped], @$snapshot)
+    );
+
+    ($err:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, @$snapshot:literal $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_err [] $err, [$($ty)::+ $(::<$($gen),+>)?], @$snapshot)
+    );
+
+    ($err:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)? $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_err [] $err, [$($ty)::+ $(::<$($gen),+>)?])
+    );
+
+    ($err:expr $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_err [] $err, [$crate::snapshot::Untyped])
+    );
+}
+
+/// Snapshots [`render_chain`] of an error with `insta::assert_snapshot!`, taking the same forms as
+/// [`assert_err_snapshot!`](crate::assert_err_snapshot). Requires the `insta` feature
+#[macro_export]
+macro_rules! assert_err_chain_snapshot {
+    (name = $name:expr; $err:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)? $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_chain [$name] $err, [$($ty)::+ $(::<$($gen),+>)?])
+    );
+
+    (name = $name:expr; $err:expr $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_chain [$name] $err, [$crate::snapshot::Untyped])
+    );
+
+    ($err:expr, @$snapshot:literal $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_chain [] $err, [$crate::snapshot::Untyped], @$snapshot)
+    );
+
+    ($err:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, @$snapshot:literal $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_chain [] $err, [$($ty)::+ $(::<$($gen),+>)?], @$snapshot)
+    );
+
+    ($err:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)? $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_chain [] $err, [$($ty)::+ $(::<$($gen),+>)?])
+    );
+
+    ($err:expr $(,)?) => (
+        $crate::assert_err_snapshot!(@assert render_chain [] $err, [$crate::snapshot::Untyped])
+    );
+}
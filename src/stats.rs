@@ -0,0 +1,173 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::error::Error;
+use core::fmt::{self, Display};
+use core::marker::PhantomData;
+
+use crate::{Downcastable, VariantName};
+
+/// Counts of errors per variant of `E`, named by [`VariantName`], for job summaries. Errors of
+/// other types are only counted per type for the types registered in advance with
+/// [`track`](ErrorStats::track), under their full type name, since the type of an error behind a
+/// trait object can't be named otherwise. All the others are counted together as `other`.
+/// Requires the `alloc` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "macros")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use std::io;
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum ImportError {
+///     #[error("duplicate row {0}")]
+///     Duplicate(u32),
+///     #[error("invalid row {row}")]
+///     Invalid { row: u32 },
+///  }
+///
+///  let errors = vec![
+///     anyhow!(ImportError::Duplicate(2)),
+///     anyhow!(ImportError::Invalid { row: 3 }),
+///     anyhow!(io::Error::other("connection reset")),
+///     anyhow!(ImportError::Duplicate(5)),
+///     anyhow!("unknown"),
+///  ];
+///
+///  let stats = ErrorStats::<ImportError>::collect(&errors);
+///  assert_eq!(stats.total(), 5);
+///  assert_eq!(stats.count("Duplicate"), 2);
+///  assert_eq!(stats.unmatched(), 2);
+///
+///  let mut stats = ErrorStats::<ImportError>::new().track::<io::Error>();
+///  stats.extend(&errors);
+///  assert_eq!(stats.count_type::<io::Error>(), 1);
+///  assert_eq!(stats.to_string(), "5 errors\n  Duplicate: 2\n  Invalid: 1\n  std::io::error::Error: 1\n  other: 1");
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ErrorStats<E> {
+    total: usize,
+    variants: BTreeMap<&'static str, usize>,
+    types: Vec<Tracked>,
+    unmatched: usize,
+    marker: PhantomData<fn() -> E>,
+}
+
+/// A type counted separately by [`ErrorStats::track`]
+#[derive(Debug)]
+struct Tracked {
+    id: TypeId,
+    name: &'static str,
+    is: fn(&(dyn Error + 'static)) -> bool,
+    count: usize,
+}
+
+impl<E: Error + Send + Sync + VariantName + 'static> ErrorStats<E> {
+    /// Creates empty statistics
+    pub fn new() -> Self {
+        ErrorStats {
+            total: 0,
+            variants: BTreeMap::new(),
+            types: Vec::new(),
+            unmatched: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Counts the errors of a collection
+    pub fn collect<I>(errors: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Downcastable,
+    {
+        let mut stats = ErrorStats::new();
+        stats.extend(errors);
+        stats
+    }
+
+    /// Counts the errors of type `T` that aren't of type `E` separately instead of as `other`, shown
+    /// under the full type name of `T`
+    pub fn track<T: Error + 'static>(mut self) -> Self {
+        self.types.push(Tracked { id: TypeId::of::<T>(), name: core::any::type_name::<T>(), is: |err| err.is::<T>(), count: 0 });
+        self
+    }
+
+    /// Counts an error
+    pub fn record<D: Downcastable + ?Sized>(&mut self, err: &D) {
+        self.total += 1;
+        if let Some(e) = err.downcast_ref::<E>() {
+            *self.variants.entry(e.variant_name()).or_default() += 1;
+            return;
+        }
+        let tracked = err
+            .to_dyn_error()
+            .and_then(|err| self.types.iter_mut().find(|tracked| (tracked.is)(err)));
+        match tracked {
+            Some(tracked) => tracked.count += 1,
+            None => self.unmatched += 1,
+        }
+    }
+
+    /// Returns the number of counted errors
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns the number of errors of the variant with the given name
+    pub fn count(&self, variant: &str) -> usize {
+        self.variants.get(variant).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of errors of a type registered with [`track`](ErrorStats::track)
+    pub fn count_type<T: ?Sized + 'static>(&self) -> usize {
+        let id = TypeId::of::<T>();
+        self.types.iter().filter(|tracked| tracked.id == id).map(|tracked| tracked.count).sum()
+    }
+
+    /// Iterates over the variants that occurred with their counts, ordered by name
+    pub fn variants(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        self.variants.iter().map(|(variant, count)| (*variant, *count))
+    }
+
+    /// Returns the number of errors that are neither of type `E` nor of a tracked type
+    pub fn unmatched(&self) -> usize {
+        self.unmatched
+    }
+}
+
+impl<E: Error + Send + Sync + VariantName + 'static> Default for ErrorStats<E> {
+    fn default() -> Self {
+        ErrorStats::new()
+    }
+}
+
+impl<E: Error + Send + Sync + VariantName + 'static, D: Downcastable> Extend<D> for ErrorStats<E> {
+    fn extend<I: IntoIterator<Item = D>>(&mut self, errors: I) {
+        for err in errors {
+            self.record(&err);
+        }
+    }
+}
+
+impl<E> Display for ErrorStats<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.total {
+            1 => f.write_str("1 error")?,
+            n => write!(f, "{n} errors")?,
+        }
+        for (variant, count) in &self.variants {
+            write!(f, "\n  {variant}: {count}")?;
+        }
+        for tracked in self.types.iter().filter(|tracked| tracked.count > 0) {
+            write!(f, "\n  {}: {}", tracked.name, tracked.count)?;
+        }
+        if self.unmatched > 0 {
+            write!(f, "\n  other: {}", self.unmatched)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+/// Maps the variants of an enum-like error type to an `http::StatusCode`, like [`match_err!`] with
+/// status codes as arms. Without a `_` arm other errors map to `500 Internal Server Error`.
+/// Requires the `http` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "http")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///  use http::StatusCode;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("invalid {0}")]
+///     Invalid(String),
+///     #[error("timeout after {secs}s")]
+///     Timeout { secs: u64 },
+///     #[error("code {0}")]
+///     Code(u16)
+///  }
+///
+///  let status = |err: anyhow::Error| err_to_status!(err, Error, {
+///     NotFound => StatusCode::NOT_FOUND,
+///     Invalid(_) => StatusCode::BAD_REQUEST,
+///     Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+///     Code(code) => StatusCode::from_u16(*code).unwrap_or(StatusCode::BAD_GATEWAY)
+///  });
+///
+///  assert_eq!(status(anyhow!(Error::NotFound)), StatusCode::NOT_FOUND);
+///  assert_eq!(status(anyhow!(Error::Timeout { secs: 5 })), StatusCode::GATEWAY_TIMEOUT);
+///  assert_eq!(status(anyhow!(Error::Code(409))), StatusCode::CONFLICT);
+///  assert_eq!(status(anyhow!("unknown")), StatusCode::INTERNAL_SERVER_ERROR);
+///
+///  let status = err_to_status!(anyhow!("unknown"), Error, {
+///     NotFound => StatusCode::NOT_FOUND,
+///     e @ _ => if e.to_string().contains("busy") {
+///         StatusCode::SERVICE_UNAVAILABLE
+///     } else {
+///         StatusCode::BAD_GATEWAY
+///     }
+///  });
+///  assert_eq!(status, StatusCode::BAD_GATEWAY);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! err_to_status {
+    (@arms $any:tt $ty:tt [$($arms:tt)*] _ => $default:expr $(,)?) => (
+        $crate::match_err!(@arms top $any $ty [] $($arms)* _ => {
+            let status: $crate::__private::http::StatusCode = $default;
+            status
+        })
+    );
+
+    (@arms $any:tt $ty:tt [$($arms:tt)*] $bind:ident @ _ => $default:expr $(,)?) => (
+        $crate::match_err!(@arms top $any $ty [] $($arms)* $bind @ _ => {
+            let status: $crate::__private::http::StatusCode = $default;
+            status
+        })
+    );
+
+    (@arms $any:tt $ty:tt [$($arms:tt)*] $(,)?) => (
+        $crate::match_err!(@arms top $any $ty [] $($arms)* _ => $crate::__private::http::StatusCode::INTERNAL_SERVER_ERROR)
+    );
+
+    (@arms $any:tt $ty:tt [$($arms:tt)*] $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::err_to_status!(@arms $any $ty [
+            $($arms)*
+            $( #[$attr] )* $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => {
+                let status: $crate::__private::http::StatusCode = $arm;
+                status
+            },
+        ] $($($rest)*)?)
+    );
+
+    ( $any:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::err_to_status!(@arms $any [$($ty)::+ $(::<$($gen),+>)?] [] $($arms)*)
+    );
+}
@@ -0,0 +1,45 @@
+/// The names of the variants of an enum-like error type, implemented by the `MatchErr` derive
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has to derive `MatchErr` to be matched by `match_err_strict!`",
+    label = "doesn't derive `MatchErr`"
+)]
+pub trait Variants {
+    const VARIANTS: &'static [&'static str];
+}
+
+/// Returns `true` if every variant is listed
+pub const fn covers(variants: &[&str], listed: &[&str]) -> bool {
+    let mut i = 0;
+    while i < variants.len() {
+        if !contains(listed, variants[i]) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn contains(names: &[&str], name: &str) -> bool {
+    let mut i = 0;
+    while i < names.len() {
+        if eq(names[i].as_bytes(), name.as_bytes()) {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
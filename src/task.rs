@@ -0,0 +1,201 @@
+//! Helpers for a `tokio::task::JoinError` found anywhere in the cause chain, telling panicked tasks
+//! from cancelled ones, and [`match_join!`](crate::match_join) for matching the result of a
+//! `JoinHandle` in one construct. Requires the `tokio` feature
+//!
+//! # Examples
+//! ```
+//! # #[cfg(feature = "tokio")]
+//! # {
+//!  use match_err::task;
+//!  use anyhow::Context;
+//!
+//!  let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//!  let err = rt.block_on(async {
+//!     tokio::spawn(async { panic!("worker crashed") }).await.context("joining worker").unwrap_err()
+//!  });
+//!
+//!  assert!(task::is_panic(&err));
+//!  assert!(!task::is_cancelled(&err));
+//!
+//!  let payload = rt.block_on(async { tokio::spawn(async { panic!("boom") }).await.unwrap_err().into_panic() });
+//!  assert_eq!(task::panic_message(&*payload), Some("boom"));
+//! # }
+//! ```
+
+use crate::Downcastable;
+use tokio::task::JoinError;
+
+/// Returns the first `JoinError` in the cause chain
+pub fn find_join_error<D: Downcastable + ?Sized>(err: &D) -> Option<&JoinError> {
+    err.find_in_chain::<JoinError>()
+}
+
+/// Returns `true` if the task panicked
+pub fn is_panic<D: Downcastable + ?Sized>(err: &D) -> bool {
+    find_join_error(err).is_some_and(JoinError::is_panic)
+}
+
+/// Returns `true` if the task was cancelled
+pub fn is_cancelled<D: Downcastable + ?Sized>(err: &D) -> bool {
+    find_join_error(err).is_some_and(JoinError::is_cancelled)
+}
+
+pub use crate::panic::panic_message;
+
+/// Matches the result of awaiting a `JoinHandle`. `cancelled` handles cancelled tasks, `panic(..)`
+/// arms handle panicked ones, either downcasting the payload with `panic(msg: String)` or binding
+/// it as `Box<dyn Any + Send>`. Given an error type, it matches the error the task returned like
+/// [`match_err!`](crate::match_err), and `Ok(..)` gets the value of the task. Everything else goes to the `_` arm.
+/// Requires the `tokio` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "tokio")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+///  let describe = |task: fn() -> anyhow::Result<u32>| rt.block_on(async move {
+///     match_join!(tokio::spawn(async move { task() }).await, Error, {
+///         Ok(n) => format!("got {n}"),
+///         NotFound => String::from("not found"),
+///         cancelled => String::from("cancelled"),
+///         panic(msg: &'static str) => format!("panicked: {msg}"),
+///         panic(_) => String::from("panicked"),
+///         _ => String::from("failed")
+///     })
+///  });
+///
+///  assert_eq!(describe(|| Ok(1)), "got 1");
+///  assert_eq!(describe(|| Err(anyhow!(Error::NotFound))), "not found");
+///  assert_eq!(describe(|| Err(anyhow!("unknown"))), "failed");
+///  assert_eq!(describe(|| panic!("boom")), "panicked: boom");
+///  assert_eq!(describe(|| std::panic::panic_any(42)), "panicked");
+///
+///  let cancelled = rt.block_on(async {
+///     let handle = tokio::spawn(std::future::pending::<()>());
+///     handle.abort();
+///     match_join!(handle.await, { cancelled => true, _ => false })
+///  });
+///  assert!(cancelled);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! match_join {
+    (@panic $payload:ident $default:tt) => ({
+        let _ = $payload;
+        $default
+    });
+
+    (@panic $payload:ident $default:tt (typed $bind:ident $ty:ty, $arm:expr) $($rest:tt)*) => (
+        match $payload.downcast::<$ty>() {
+            Ok($bind) => {
+                let $bind: $ty = *$bind;
+                $arm
+            }
+            Err($payload) => $crate::match_join!(@panic $payload $default $($rest)*),
+        }
+    );
+
+    (@panic $payload:ident $default:tt (any $bind:pat_param, $arm:expr) $($rest:tt)*) => ({
+        let $bind = $payload;
+        $arm
+    });
+
+    (@ok none $res:ident [] [] $default:tt) => ({
+        let _ = $res;
+        $default
+    });
+
+    (@ok none $res:ident [($($ok:tt)+) $ok_arm:expr] [] $default:tt) => (
+        match $res {
+            $($ok)+ => $ok_arm,
+            #[allow(unreachable_patterns)]
+            _ => $default,
+        }
+    );
+
+    (@ok none $res:ident [$($ok:tt)*] [$($arms:tt)+] $default:tt) => (
+        compile_error!("the variant arms of match_join! need the error type of the task")
+    );
+
+    (@ok $ty:tt $res:ident [] [$($arms:tt)*] $default:tt) => (
+        match $res {
+            Ok(_) => $default,
+            Err(ref e) => $crate::match_err!(@arms top (*e) $ty [] $($arms)* _ => $default),
+        }
+    );
+
+    (@ok $ty:tt $res:ident [($($ok:tt)+) $ok_arm:expr] [$($arms:tt)*] $default:tt) => (
+        match $res {
+            Ok($($ok)+) => $ok_arm,
+            #[allow(unreachable_patterns)]
+            Ok(_) => $default,
+            Err(ref e) => $crate::match_err!(@arms top (*e) $ty [] $($arms)* _ => $default),
+        }
+    );
+
+    (@cancelled [] $default:tt) => ($default);
+
+    (@cancelled [$arm:expr] $default:tt) => ($arm);
+
+    (@match $joined:tt $ty:tt $ok:tt $cancelled:tt [$($panic:tt)*] $arms:tt $default:tt) => (
+        match $joined {
+            Ok(res) => $crate::match_join!(@ok $ty res $ok $arms $default),
+            Err(join_err) => if join_err.is_cancelled() {
+                $crate::match_join!(@cancelled $cancelled $default)
+            } else {
+                match join_err.try_into_panic() {
+                    Ok(payload) => $crate::match_join!(@panic payload $default $($panic)*),
+                    Err(_) => $default,
+                }
+            },
+        }
+    );
+
+    (@arms $joined:tt $ty:tt $ok:tt $cancelled:tt $panic:tt $arms:tt _ => $default:expr $(,)?) => (
+        $crate::match_join!(@match $joined $ty $ok $cancelled $panic $arms { $default })
+    );
+
+    (@arms $joined:tt $ty:tt $ok:tt $cancelled:tt $panic:tt $arms:tt $(,)?) => (
+        $crate::match_join!(@match $joined $ty $ok $cancelled $panic $arms {})
+    );
+
+    (@arms $joined:tt $ty:tt [] $cancelled:tt $panic:tt $arms:tt Ok( $($value:tt)+ ) => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_join!(@arms $joined $ty [($($value)+) $arm] $cancelled $panic $arms $($($rest)*)?)
+    );
+
+    (@arms $joined:tt $ty:tt $ok:tt [] $panic:tt $arms:tt cancelled => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_join!(@arms $joined $ty $ok [$arm] $panic $arms $($($rest)*)?)
+    );
+
+    (@arms $joined:tt $ty:tt $ok:tt $cancelled:tt [$($panic:tt)*] $arms:tt panic( $bind:ident : $pty:ty ) => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_join!(@arms $joined $ty $ok $cancelled [$($panic)* (typed $bind $pty, $arm)] $arms $($($rest)*)?)
+    );
+
+    (@arms $joined:tt $ty:tt $ok:tt $cancelled:tt [$($panic:tt)*] $arms:tt panic( $bind:pat_param ) => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_join!(@arms $joined $ty $ok $cancelled [$($panic)* (any $bind, $arm)] $arms $($($rest)*)?)
+    );
+
+    (@arms $joined:tt $ty:tt $ok:tt $cancelled:tt $panic:tt [$($arms:tt)*] $( #[$attr:meta] )* $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($fields:tt)* } )? )|+ $( if $guard:expr )? => $arm:expr $(, $($rest:tt)*)?) => (
+        $crate::match_join!(@arms $joined $ty $ok $cancelled $panic [
+            $($arms)*
+            $( #[$attr] )* $( $variant $( ( $($inner)+ ) )? $( { $($fields)* } )? )|+ $( if $guard )? => $arm,
+        ] $($($rest)*)?)
+    );
+
+    ( $joined:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::match_join!(@arms $joined [$($ty)::+ $(::<$($gen),+>)?] [] [] [] [] $($arms)*)
+    );
+
+    ( $joined:expr, { $($arms:tt)* } ) => (
+        $crate::match_join!(@arms $joined none [] [] [] [] $($arms)*)
+    );
+}
@@ -0,0 +1,174 @@
+use crate::{Downcastable, VariantName};
+use core::fmt::Display;
+use std::error::Error;
+
+/// Emits tracing events for the errors of results. Requires the `tracing` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "tracing")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, MatchErr, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///  }
+///
+///  let res: anyhow::Result<()> = Err(anyhow!(Error::NotFound));
+///  // emits an error event with `error.type` and `error.variant = "NotFound"`
+///  let res = res.trace_variants::<Error>();
+///  assert!(res.is_err());
+/// # }
+/// ```
+pub trait TraceResultExt: Sized {
+    /// Emits an error event with the `error.type` and `error.variant` fields if the result is an
+    /// error of type `E`, and with the type name of the error otherwise. Returns the result unchanged
+    fn trace_variants<E>(self) -> Self
+    where
+        E: Error + Send + Sync + VariantName + 'static;
+}
+
+impl<T, D: Downcastable + Display> TraceResultExt for Result<T, D> {
+    fn trace_variants<E>(self) -> Self
+    where
+        E: Error + Send + Sync + VariantName + 'static,
+    {
+        if let Err(err) = &self {
+            match err.downcast_ref::<E>() {
+                Some(e) => tracing::error!(
+                    error.type = core::any::type_name::<E>(),
+                    error.variant = e.variant_name(),
+                    message = %err,
+                ),
+                None => tracing::error!(error.type = core::any::type_name::<D>(), message = %err),
+            }
+        }
+        self
+    }
+}
+
+/// Emits a tracing event for the error of a result and returns the result unchanged. The event
+/// has the `error.type` and `error.variant` fields, and the matching arm adds fields of the payload,
+/// written like fields of `tracing::event!`. Errors of other types only get `error.type`.
+/// The variant name comes from [`VariantName`](crate::VariantName), types that don't implement it
+/// fall back to their `Debug` rendering, which has to be derived.
+/// The level is `ERROR` unless given first. Requires the `tracing` feature
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "tracing")]
+/// # {
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("code {0}")]
+///     Code(u16),
+///     #[error("timeout after {secs}s on {op}")]
+///     Timeout { secs: u64, op: String }
+///  }
+///
+///  let (events, res) = capture(|| trace_err!(Err::<(), _>(anyhow!(Error::Code(503))), Error, {
+///     Code(code) => { http.status = *code },
+///     Timeout { secs, op } => { timeout.secs = *secs, timeout.op = %op }
+///  }));
+///  assert!(res.is_err());
+///  assert!(events[0].ends_with("::Error error.variant=Code message=code 503 http.status=503"));
+///
+///  let (events, _) = capture(|| trace_err!(WARN, Err::<(), _>(anyhow!(Error::NotFound)), Error, {
+///     Code(code) => { http.status = *code }
+///  }));
+///  assert!(events[0].contains("error.variant=NotFound message=not found"));
+///
+///  let (events, _) = capture(|| trace_err!(Err::<(), _>(anyhow!("unknown")), Error, {}));
+///  assert_eq!(events, ["error.type=anyhow::Error message=unknown"]);
+///
+///  let (events, res) = capture(|| trace_err!(Ok::<_, anyhow::Error>(1), Error, {}));
+///  assert!(events.is_empty());
+///  assert_eq!(res.unwrap(), 1);
+///
+///  # fn capture<R>(f: impl FnOnce() -> R) -> (Vec<String>, R) {
+///  #     use std::sync::{Arc, Mutex};
+///  #     use tracing::field::{Field, Visit};
+///  #     struct Fields(String);
+///  #     impl Visit for Fields {
+///  #         fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+///  #             if !self.0.is_empty() { self.0.push(' '); }
+///  #             self.0 += &format!("{}={:?}", field.name(), value).replace('"', "");
+///  #         }
+///  #     }
+///  #     struct Capture(Arc<Mutex<Vec<String>>>);
+///  #     impl tracing::Subscriber for Capture {
+///  #         fn enabled(&self, _: &tracing::Metadata<'_>) -> bool { true }
+///  #         fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id { tracing::span::Id::from_u64(1) }
+///  #         fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+///  #         fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+///  #         fn event(&self, event: &tracing::Event<'_>) {
+///  #             let mut fields = Fields(String::new());
+///  #             event.record(&mut fields);
+///  #             self.0.lock().unwrap().push(fields.0);
+///  #         }
+///  #         fn enter(&self, _: &tracing::span::Id) {}
+///  #         fn exit(&self, _: &tracing::span::Id) {}
+///  #     }
+///  #     let events = Arc::new(Mutex::new(Vec::new()));
+///  #     let res = tracing::subscriber::with_default(Capture(events.clone()), f);
+///  #     let events = events.lock().unwrap().clone();
+///  #     (events, res)
+///  # }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! trace_err {
+    (@event $level:ident, $($fields:tt)*) => (
+        $crate::__private::tracing::event!($crate::__private::tracing::Level::$level, $($fields)*)
+    );
+
+    (@trace $level:ident, $res:expr, $ty:tt, { $( $( $variant:ident $( ( $($inner:tt)+ ) )? $( { $($pfields:tt)* } )? )|+ $( if $guard:expr )? => { $($fields:tt)* } ),* $(,)? } ) => (
+        match $res {
+            result => {
+                if let Err(err) = &result {
+                    match $crate::__match_err_probe!(match_err_downcast err, $crate::__match_err_path!($ty)) {
+                        Some(e) => {
+                            let variant = $crate::__match_err_variant_name!(e);
+                            match e {
+                                $(
+                                    $( $crate::__match_err_path!($ty $variant $( ( $($inner)+ ) )? $( { $($pfields)* } )?) )|+ $( if $guard )? => $crate::trace_err!(@event $level,
+                                        error.type = ::core::any::type_name::<$crate::__match_err_path!($ty)>(),
+                                        error.variant = &*variant,
+                                        message = %err,
+                                        $($fields)*
+                                    ),
+                                )*
+                                _ => $crate::trace_err!(@event $level,
+                                    error.type = ::core::any::type_name::<$crate::__match_err_path!($ty)>(),
+                                    error.variant = &*variant,
+                                    message = %err,
+                                ),
+                            }
+                        }
+                        None => $crate::trace_err!(@event $level,
+                            error.type = ::core::any::type_name_of_val(err),
+                            message = %err,
+                        ),
+                    }
+                }
+                result
+            }
+        }
+    );
+
+    ( $level:ident, $res:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::trace_err!(@trace $level, $res, [$($ty)::+ $(::<$($gen),+>)?], { $($arms)* })
+    );
+
+    ( $res:expr, $($ty:ident)::+ $(::)? $(< $($gen:ty),+ $(,)? >)?, { $($arms:tt)* } ) => (
+        $crate::trace_err!(ERROR, $res, $($ty)::+ $(::<$($gen),+>)?, { $($arms)* })
+    );
+}
@@ -0,0 +1,116 @@
+use core::error::Error;
+use core::fmt::{self, Debug};
+#[cfg(feature = "alloc")]
+use core::fmt::Display;
+
+#[cfg(feature = "alloc")]
+use crate::{ErrorMismatch, Mismatch};
+use crate::Downcastable;
+
+/// A pattern of an enum-like error type as a value, built with [`variant!`](crate::variant). Unlike
+/// the pattern-taking macros it can be stored, passed around and used as a parameter of table
+/// tests, like an rstest `#[case]`
+///
+/// # Examples
+/// ```
+///  use match_err::*;
+///  use anyhow::anyhow;
+///
+///  #[derive(thiserror::Error, Debug)]
+///  enum Error {
+///     #[error("not found")]
+///     NotFound,
+///     #[error("custom: {0}")]
+///     Custom(String),
+///     #[error("timeout after {secs}s")]
+///     Timeout { secs: u64 },
+///  }
+///
+///  fn check(err: anyhow::Error, expected: VariantMatcher<Error>) {
+///     expected.assert(&err);
+///  }
+///
+///  check(anyhow!(Error::NotFound), variant!(Error::NotFound));
+///  check(anyhow!(Error::Custom("disk".into())), variant!(Error::Custom(_)));
+///  check(anyhow!(Error::Timeout { secs: 40 }), variant!(Error::Timeout { secs } if *secs > 30));
+///
+///  let matcher = variant!(Error::Custom(msg) if msg == "disk");
+///  assert_eq!(matcher.pattern(), "Error::Custom(msg) if msg == \"disk\"");
+///  assert!(matcher.matches(&anyhow!(Error::Custom("disk".into()))));
+///  assert!(!matcher.matches(&anyhow!("disk")));
+///
+///  let mismatch = matcher.check(&anyhow!(Error::Custom("cpu".into()))).unwrap_err();
+///  assert_eq!(mismatch.kind(), Mismatch::Payload);
+///  let mismatch = matcher.check(&anyhow!(Error::NotFound)).unwrap_err();
+///  assert_eq!(mismatch.kind(), Mismatch::Variant);
+/// ```
+pub struct VariantMatcher<E> {
+    pattern: &'static str,
+    matches: fn(&E) -> bool,
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+    is_variant: fn(&E) -> bool,
+}
+
+impl<E> VariantMatcher<E> {
+    #[doc(hidden)]
+    pub const fn new(pattern: &'static str, matches: fn(&E) -> bool, is_variant: fn(&E) -> bool) -> Self {
+        VariantMatcher { pattern, matches, is_variant }
+    }
+
+    /// Returns the pattern as it was written
+    pub fn pattern(&self) -> &'static str {
+        self.pattern
+    }
+}
+
+impl<E: Error + Send + Sync + 'static> VariantMatcher<E> {
+    /// Returns `true` if the error is of the type and matches the pattern
+    pub fn matches<D: Downcastable + ?Sized>(&self, err: &D) -> bool {
+        err.downcast_ref::<E>().is_some_and(self.matches)
+    }
+
+    /// Checks the error against the pattern, telling a wrong type from a wrong variant or a
+    /// wrong payload. Requires the `alloc` feature
+    #[cfg(feature = "alloc")]
+    #[allow(clippy::result_large_err)]
+    pub fn check<D: Downcastable + Display + ?Sized>(&self, err: &D) -> Result<(), ErrorMismatch> {
+        let actual = err.downcast_ref::<E>();
+        let kind = match actual {
+            Some(e) if (self.matches)(e) => return Ok(()),
+            Some(e) if (self.is_variant)(e) => Mismatch::Payload,
+            Some(_) => Mismatch::Variant,
+            None => Mismatch::Type,
+        };
+        Err(ErrorMismatch::new(
+            kind,
+            &format_args!("{}", self.pattern),
+            actual.map(|e| e as &dyn Debug),
+            err,
+            err.to_dyn_error(),
+        ))
+    }
+
+    /// Asserts the error matches the pattern like [`assert_err_matches!`](crate::assert_err_matches).
+    /// Requires the `alloc` feature
+    #[cfg(feature = "alloc")]
+    #[track_caller]
+    pub fn assert<D: Downcastable + Display + ?Sized>(&self, err: &D) {
+        if let Err(mismatch) = self.check(err) {
+            crate::assert::assert_error_failed(&mismatch, None)
+        }
+    }
+}
+
+impl<E> Clone for VariantMatcher<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for VariantMatcher<E> {}
+
+impl<E> Debug for VariantMatcher<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "variant!({})", self.pattern)
+    }
+}